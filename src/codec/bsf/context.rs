@@ -0,0 +1,190 @@
+use std::ffi::CString;
+use std::ptr;
+
+use super::Filter;
+use codec::{packet, Parameters, Status};
+use ffi::*;
+use libc::{c_void, EAGAIN};
+use {Dictionary, Error, Packet, Rational};
+
+pub struct Context {
+    ptr: *mut AVBSFContext,
+}
+
+unsafe impl Send for Context {}
+
+impl Context {
+    pub unsafe fn wrap(ptr: *mut AVBSFContext) -> Self {
+        Context { ptr: ptr }
+    }
+
+    pub unsafe fn as_ptr(&self) -> *const AVBSFContext {
+        self.ptr as *const _
+    }
+
+    pub unsafe fn as_mut_ptr(&mut self) -> *mut AVBSFContext {
+        self.ptr
+    }
+}
+
+impl Context {
+    pub fn new<R: Into<Rational>>(
+        filter: &Filter,
+        parameters: &Parameters,
+        time_base: R,
+    ) -> Result<Self, Error> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+
+            match av_bsf_alloc(filter.as_ptr(), &mut ptr) {
+                0 => Context::setup(ptr, parameters, time_base.into()),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    // filters separated by `,` with options after `=`, for example
+    // "h264_mp4toannexb,dump_extra=freq=keyframe"
+    pub fn parse<R: Into<Rational>>(
+        filters: &str,
+        parameters: &Parameters,
+        time_base: R,
+    ) -> Result<Self, Error> {
+        unsafe {
+            let filters = CString::new(filters).unwrap();
+            let mut ptr = ptr::null_mut();
+
+            match av_bsf_list_parse_str(filters.as_ptr(), &mut ptr) {
+                0 => Context::setup(ptr, parameters, time_base.into()),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn chain<R: Into<Rational>>(
+        filters: &[&str],
+        parameters: &Parameters,
+        time_base: R,
+    ) -> Result<Self, Error> {
+        Context::parse(&filters.join(","), parameters, time_base)
+    }
+
+    unsafe fn setup(
+        ptr: *mut AVBSFContext,
+        parameters: &Parameters,
+        time_base: Rational,
+    ) -> Result<Self, Error> {
+        // freed on drop if copying the parameters fails
+        let context = Context::wrap(ptr);
+
+        match avcodec_parameters_copy((*ptr).par_in, parameters.as_ptr()) {
+            e if e < 0 => Err(Error::from(e)),
+            _ => {
+                (*ptr).time_base_in = time_base.into();
+                Ok(context)
+            }
+        }
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        unsafe {
+            let name = CString::new(name).unwrap();
+            let value = CString::new(value).unwrap();
+
+            match av_opt_set(
+                self.as_mut_ptr() as *mut c_void,
+                name.as_ptr(),
+                value.as_ptr(),
+                AV_OPT_SEARCH_CHILDREN,
+            ) {
+                0 => Ok(()),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn set_options<'a>(&mut self, options: Dictionary<'a>) -> Result<Dictionary<'a>, Error> {
+        unsafe {
+            let mut opts = options.disown();
+            let res = av_opt_set_dict2(
+                self.as_mut_ptr() as *mut c_void,
+                &mut opts,
+                AV_OPT_SEARCH_CHILDREN,
+            );
+            let options = Dictionary::own(opts);
+
+            match res {
+                0 => Ok(options),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn init(&mut self) -> Result<(), Error> {
+        unsafe {
+            match av_bsf_init(self.as_mut_ptr()) {
+                0 => Ok(()),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn parameters(&self) -> Parameters {
+        unsafe {
+            let mut parameters = Parameters::new();
+            avcodec_parameters_copy(parameters.as_mut_ptr(), (*self.as_ptr()).par_out);
+
+            parameters
+        }
+    }
+
+    pub fn time_base(&self) -> Rational {
+        unsafe { Rational::from((*self.as_ptr()).time_base_out) }
+    }
+
+    // the packet data is moved into the filter, leaving `packet` empty
+    pub fn send(&mut self, packet: &mut Packet) -> Result<Status, Error> {
+        unsafe {
+            match av_bsf_send_packet(self.as_mut_ptr(), packet::Mut::as_mut_ptr(packet)) {
+                0 => Ok(Status::Ready),
+                e if e == AVERROR(EAGAIN) => Ok(Status::Again),
+                AVERROR_EOF => Ok(Status::Drained),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn send_eof(&mut self) -> Result<(), Error> {
+        unsafe {
+            match av_bsf_send_packet(self.as_mut_ptr(), ptr::null_mut()) {
+                0 | AVERROR_EOF => Ok(()),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn receive(&mut self, packet: &mut Packet) -> Result<Status, Error> {
+        unsafe {
+            match av_bsf_receive_packet(self.as_mut_ptr(), packet::Mut::as_mut_ptr(packet)) {
+                0 => Ok(Status::Ready),
+                e if e == AVERROR(EAGAIN) => Ok(Status::Again),
+                AVERROR_EOF => Ok(Status::Drained),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn flush(&mut self) {
+        unsafe {
+            av_bsf_flush(self.as_mut_ptr());
+        }
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe {
+            av_bsf_free(&mut self.ptr);
+        }
+    }
+}
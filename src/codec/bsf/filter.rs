@@ -0,0 +1,62 @@
+use std::ffi::CStr;
+use std::ptr;
+use std::str::from_utf8_unchecked;
+
+use ffi::*;
+use libc::c_void;
+
+pub struct Filter {
+    ptr: *const AVBitStreamFilter,
+}
+
+unsafe impl Send for Filter {}
+
+impl Filter {
+    pub unsafe fn wrap(ptr: *const AVBitStreamFilter) -> Self {
+        Filter { ptr: ptr }
+    }
+
+    pub unsafe fn as_ptr(&self) -> *const AVBitStreamFilter {
+        self.ptr
+    }
+}
+
+impl Filter {
+    pub fn name(&self) -> &str {
+        unsafe { from_utf8_unchecked(CStr::from_ptr((*self.as_ptr()).name).to_bytes()) }
+    }
+}
+
+pub struct Iter {
+    opaque: *mut c_void,
+}
+
+impl Iter {
+    pub fn new() -> Self {
+        Iter {
+            opaque: ptr::null_mut(),
+        }
+    }
+}
+
+impl Default for Iter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for Iter {
+    type Item = Filter;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        unsafe {
+            let ptr = av_bsf_iterate(&mut self.opaque);
+
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Filter::wrap(ptr))
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+pub mod filter;
+pub use self::filter::{Filter, Iter};
+
+pub mod context;
+pub use self::context::Context;
+
+use std::ffi::CString;
+
+use ffi::*;
+
+pub fn list() -> Iter {
+    Iter::new()
+}
+
+pub fn find(name: &str) -> Option<Filter> {
+    unsafe {
+        let name = CString::new(name).unwrap();
+        let ptr = av_bsf_get_by_name(name.as_ptr());
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Filter::wrap(ptr))
+        }
+    }
+}
@@ -3,11 +3,12 @@ use std::ops::{Deref, DerefMut};
 use ffi::*;
 use libc::c_int;
 
-use super::Opened;
+use super::{seek, Opened};
 use codec::Context;
+use format::context::Input;
 use frame;
 use util::format;
-use {packet, AudioService, ChannelLayout, Error};
+use {packet, AudioService, ChannelLayout, Error, Rational, Rescale};
 
 pub struct Audio(pub Opened);
 
@@ -32,6 +33,89 @@ impl Audio {
         }
     }
 
+    pub fn seek_exact(
+        &mut self,
+        input: &mut Input,
+        stream: usize,
+        pts: i64,
+        out: &mut frame::Audio,
+    ) -> Result<(), Error> {
+        let time_base = input
+            .stream(stream)
+            .ok_or(Error::StreamNotFound)?
+            .time_base();
+
+        self.flush();
+
+        let mut decoded = frame::Audio::empty();
+        let mut start = 0;
+
+        seek::forward(input, stream, pts, |packet| {
+            if !self.decode(packet, &mut decoded)? {
+                return Ok(None);
+            }
+
+            let ts = match decoded.timestamp() {
+                Some(ts) => ts,
+                None => return Ok(Some(false)),
+            };
+
+            let rate = Rational(1, decoded.rate() as i32);
+            let end = ts + (decoded.samples() as i64).rescale(rate, time_base);
+
+            if end <= pts {
+                return Ok(Some(false));
+            }
+
+            start = ts;
+            Ok(Some(true))
+        })?;
+
+        let rate = Rational(1, decoded.rate() as i32);
+        let offset = if pts > start {
+            (pts - start).rescale(time_base, rate) as usize
+        } else {
+            0
+        };
+
+        if offset == 0 || offset >= decoded.samples() {
+            *out = decoded;
+            return Ok(());
+        }
+
+        unsafe {
+            let samples = decoded.samples() - offset;
+            let mut trimmed = frame::Audio::empty();
+
+            trimmed.set_format(decoded.format());
+            trimmed.set_channel_layout(decoded.channel_layout());
+            trimmed.set_channels(decoded.channels());
+            trimmed.set_rate(decoded.rate());
+            trimmed.set_samples(samples);
+
+            match av_frame_get_buffer(trimmed.as_mut_ptr(), 0) {
+                e if e < 0 => return Err(Error::from(e)),
+                _ => (),
+            }
+
+            av_frame_copy_props(trimmed.as_mut_ptr(), decoded.as_ptr());
+            av_samples_copy(
+                (*trimmed.as_mut_ptr()).extended_data,
+                (*decoded.as_ptr()).extended_data as *const *mut _,
+                0,
+                offset as c_int,
+                samples as c_int,
+                decoded.channels() as c_int,
+                decoded.format().into(),
+            );
+
+            trimmed.set_pts(Some(pts));
+            *out = trimmed;
+        }
+
+        Ok(())
+    }
+
     pub fn rate(&self) -> u32 {
         unsafe { (*self.as_ptr()).sample_rate as u32 }
     }
@@ -35,16 +35,16 @@ impl Decoder {
         mut self,
         codec: D,
         options: Dictionary,
-    ) -> Result<Opened, Error> {
+    ) -> Result<(Opened, Dictionary), Error> {
         unsafe {
             if let Some(codec) = codec.decoder() {
                 let mut opts = options.disown();
                 let res = avcodec_open2(self.as_mut_ptr(), codec.as_ptr(), &mut opts);
 
-                Dictionary::own(opts);
+                let options = Dictionary::own(opts);
 
                 match res {
-                    0 => Ok(Opened(self)),
+                    0 => Ok((Opened(self), options)),
                     e => Err(Error::from(e)),
                 }
             } else {
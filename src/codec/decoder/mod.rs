@@ -21,6 +21,8 @@ pub use self::check::Check;
 pub mod opened;
 pub use self::opened::Opened;
 
+mod seek;
+
 use std::ffi::CString;
 
 use codec::Context;
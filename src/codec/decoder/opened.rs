@@ -1,10 +1,12 @@
 use std::ops::{Deref, DerefMut};
 
+use std::ptr;
+
 use super::{Audio, Decoder, Subtitle, Video};
-use codec::{Context, Profile};
+use codec::{packet, Context, Profile, Status};
 use ffi::*;
-use media;
-use {Error, Rational};
+use libc::EAGAIN;
+use {media, Error, Frame, Rational};
 
 pub struct Opened(pub Decoder);
 
@@ -62,6 +64,45 @@ impl Opened {
             avcodec_flush_buffers(self.as_mut_ptr());
         }
     }
+
+    pub fn send_packet<P: packet::Ref + ?Sized>(&mut self, packet: &P) -> Result<Status, Error> {
+        unsafe {
+            match avcodec_send_packet(self.as_mut_ptr(), packet.as_ptr()) {
+                0 => Ok(Status::Ready),
+                e if e == AVERROR(EAGAIN) => Ok(Status::Again),
+                AVERROR_EOF => Ok(Status::Drained),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn send_eof(&mut self) -> Result<(), Error> {
+        unsafe {
+            match avcodec_send_packet(self.as_mut_ptr(), ptr::null()) {
+                0 | AVERROR_EOF => Ok(()),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn receive_frame(&mut self, frame: &mut Frame) -> Result<Status, Error> {
+        unsafe {
+            match avcodec_receive_frame(self.as_mut_ptr(), frame.as_mut_ptr()) {
+                0 => Ok(Status::Ready),
+                e if e == AVERROR(EAGAIN) => Ok(Status::Again),
+                AVERROR_EOF => Ok(Status::Drained),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+
+    pub fn frames(&mut self) -> Frames {
+        Frames::new(self, None)
+    }
+
+    pub fn decode_frames<'a>(&'a mut self, packet: &'a dyn packet::Ref) -> Frames<'a> {
+        Frames::new(self, Some(packet))
+    }
 }
 
 impl Drop for Opened {
@@ -97,3 +138,47 @@ impl AsMut<Context> for Opened {
         &mut self.0
     }
 }
+
+// Yields the frames already waiting in the decoder, then sends the packet (if
+// any) and yields what it produced, so no frame is lost if the decoder was not
+// fully drained before.
+pub struct Frames<'a> {
+    decoder: &'a mut Opened,
+    packet: Option<&'a dyn packet::Ref>,
+}
+
+impl<'a> Frames<'a> {
+    pub fn new(decoder: &'a mut Opened, packet: Option<&'a dyn packet::Ref>) -> Self {
+        Frames {
+            decoder: decoder,
+            packet: packet,
+        }
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<Frame, Error>;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        loop {
+            let mut frame = unsafe { Frame::empty() };
+
+            match self.decoder.receive_frame(&mut frame) {
+                Ok(Status::Ready) => return Some(Ok(frame)),
+
+                Ok(Status::Again) => match self.packet.take() {
+                    Some(packet) => match self.decoder.send_packet(packet) {
+                        Ok(Status::Ready) => (),
+                        Ok(..) => return None,
+                        Err(e) => return Some(Err(e)),
+                    },
+
+                    None => return None,
+                },
+
+                Ok(Status::Drained) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use format::context::Input;
+use format::seek;
+use {Error, Packet};
+
+// Seeks `stream` to the keyframe preceding `pts` and feeds its packets to
+// `decode`, followed by empty packets to drain the decoder once the input is
+// exhausted.
+//
+// `decode` returns `None` when no frame came out, `Some(true)` once the target
+// has been reached and `Some(false)` for frames that must be discarded.
+pub fn forward<F>(input: &mut Input, stream: usize, pts: i64, mut decode: F) -> Result<(), Error>
+where
+    F: FnMut(&Packet) -> Result<Option<bool>, Error>,
+{
+    input.seek_stream(Some(stream), pts, .., seek::BACKWARD)?;
+
+    loop {
+        let mut packet = Packet::empty();
+
+        match packet.read(input) {
+            Ok(..) if packet.stream() != stream => continue,
+            Ok(..) => {
+                if let Some(true) = decode(&packet)? {
+                    return Ok(());
+                }
+            }
+
+            Err(Error::Eof) => loop {
+                match decode(&Packet::empty())? {
+                    Some(true) => return Ok(()),
+                    Some(false) => (),
+                    None => return Err(Error::Eof),
+                }
+            },
+
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -3,9 +3,10 @@ use std::ops::{Deref, DerefMut};
 use ffi::*;
 use libc::c_int;
 
-use super::{slice, Opened};
+use super::{seek, slice, Opened};
 use codec::Context;
 use color;
+use format::context::Input;
 use frame;
 use util::chroma;
 use util::format;
@@ -34,6 +35,28 @@ impl Video {
         }
     }
 
+    pub fn seek_exact(
+        &mut self,
+        input: &mut Input,
+        stream: usize,
+        pts: i64,
+        out: &mut frame::Video,
+    ) -> Result<(), Error> {
+        self.flush();
+
+        seek::forward(input, stream, pts, |packet| {
+            if !self.decode(packet, out)? {
+                return Ok(None);
+            }
+
+            Ok(Some(match out.timestamp() {
+                Some(ts) if out.packet().duration > 0 => ts + out.packet().duration > pts,
+                Some(ts) => ts >= pts,
+                None => false,
+            }))
+        })
+    }
+
     pub fn width(&self) -> u32 {
         unsafe { (*self.as_ptr()).width as u32 }
     }
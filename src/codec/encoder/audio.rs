@@ -5,7 +5,7 @@ use ffi::*;
 use libc::c_int;
 
 use super::Encoder as Super;
-use codec::{traits, Context};
+use codec::{traits, Context, Status};
 use util::format;
 use {frame, packet, ChannelLayout, Dictionary, Error};
 
@@ -34,15 +34,15 @@ impl Audio {
         }
     }
 
-    pub fn open_with(mut self, options: Dictionary) -> Result<Encoder, Error> {
+    pub fn open_with(mut self, options: Dictionary) -> Result<(Encoder, Dictionary), Error> {
         unsafe {
             let mut opts = options.disown();
             let res = avcodec_open2(self.as_mut_ptr(), ptr::null(), &mut opts);
 
-            Dictionary::own(opts);
+            let options = Dictionary::own(opts);
 
             match res {
-                0 => Ok(Encoder(self)),
+                0 => Ok((Encoder(self), options)),
                 e => Err(Error::from(e)),
             }
         }
@@ -52,16 +52,16 @@ impl Audio {
         mut self,
         codec: E,
         options: Dictionary,
-    ) -> Result<Encoder, Error> {
+    ) -> Result<(Encoder, Dictionary), Error> {
         unsafe {
             if let Some(codec) = codec.encoder() {
                 let mut opts = options.disown();
                 let res = avcodec_open2(self.as_mut_ptr(), codec.as_ptr(), &mut opts);
 
-                Dictionary::own(opts);
+                let options = Dictionary::own(opts);
 
                 match res {
-                    0 => Ok(Encoder(self)),
+                    0 => Ok((Encoder(self), options)),
                     e => Err(Error::from(e)),
                 }
             } else {
@@ -180,6 +180,24 @@ impl Encoder {
         }
     }
 
+    pub fn send_frame(&mut self, frame: &frame::Audio) -> Result<Status, Error> {
+        unsafe {
+            if self.format() != frame.format() {
+                return Err(Error::InvalidData);
+            }
+
+            super::send_frame(self.0.as_mut_ptr(), frame.as_ptr())
+        }
+    }
+
+    pub fn send_eof(&mut self) -> Result<(), Error> {
+        unsafe { super::send_frame(self.0.as_mut_ptr(), ptr::null()).map(|_| ()) }
+    }
+
+    pub fn receive_packet<P: packet::Mut>(&mut self, out: &mut P) -> Result<Status, Error> {
+        unsafe { super::receive_packet(self.0.as_mut_ptr(), out.as_mut_ptr()) }
+    }
+
     pub fn frame_size(&self) -> u32 {
         unsafe { (*self.as_ptr()).frame_size as u32 }
     }
@@ -25,9 +25,10 @@ pub use self::decision::Decision;
 use std::ffi::CString;
 
 use codec::Context;
-use codec::Id;
+use codec::{Id, Status};
 use ffi::*;
-use Codec;
+use libc::EAGAIN;
+use {Codec, Error};
 
 pub fn new() -> Encoder {
     Context::new().encoder()
@@ -57,3 +58,26 @@ pub fn find_by_name(name: &str) -> Option<Codec> {
         }
     }
 }
+
+// a null frame signals end of stream
+unsafe fn send_frame(context: *mut AVCodecContext, frame: *const AVFrame) -> Result<Status, Error> {
+    match avcodec_send_frame(context, frame) {
+        0 => Ok(Status::Ready),
+        e if e == AVERROR(EAGAIN) => Ok(Status::Again),
+        AVERROR_EOF if frame.is_null() => Ok(Status::Ready),
+        AVERROR_EOF => Ok(Status::Drained),
+        e => Err(Error::from(e)),
+    }
+}
+
+unsafe fn receive_packet(
+    context: *mut AVCodecContext,
+    packet: *mut AVPacket,
+) -> Result<Status, Error> {
+    match avcodec_receive_packet(context, packet) {
+        0 => Ok(Status::Ready),
+        e if e == AVERROR(EAGAIN) => Ok(Status::Again),
+        AVERROR_EOF => Ok(Status::Drained),
+        e => Err(Error::from(e)),
+    }
+}
@@ -37,16 +37,16 @@ impl Subtitle {
         mut self,
         codec: E,
         options: Dictionary,
-    ) -> Result<Encoder, Error> {
+    ) -> Result<(Encoder, Dictionary), Error> {
         unsafe {
             if let Some(codec) = codec.encoder() {
                 let mut opts = options.disown();
                 let res = avcodec_open2(self.as_mut_ptr(), codec.as_ptr(), &mut opts);
 
-                Dictionary::own(opts);
+                let options = Dictionary::own(opts);
 
                 match res {
-                    0 => Ok(Encoder(self)),
+                    0 => Ok((Encoder(self), options)),
                     e => Err(Error::from(e)),
                 }
             } else {
@@ -6,7 +6,7 @@ use libc::{c_float, c_int};
 
 use super::Encoder as Super;
 use super::{Comparison, Decision, MotionEstimation, Prediction};
-use codec::{traits, Context};
+use codec::{traits, Context, Status};
 use {color, format, frame, packet, Dictionary, Error, Rational};
 
 pub struct Video(pub Super);
@@ -37,15 +37,15 @@ impl Video {
     }
 
     #[inline]
-    pub fn open_with(mut self, options: Dictionary) -> Result<Encoder, Error> {
+    pub fn open_with(mut self, options: Dictionary) -> Result<(Encoder, Dictionary), Error> {
         unsafe {
             let mut opts = options.disown();
             let res = avcodec_open2(self.as_mut_ptr(), ptr::null(), &mut opts);
 
-            Dictionary::own(opts);
+            let options = Dictionary::own(opts);
 
             match res {
-                0 => Ok(Encoder(self)),
+                0 => Ok((Encoder(self), options)),
                 e => Err(Error::from(e)),
             }
         }
@@ -56,16 +56,16 @@ impl Video {
         mut self,
         codec: E,
         options: Dictionary,
-    ) -> Result<Encoder, Error> {
+    ) -> Result<(Encoder, Dictionary), Error> {
         unsafe {
             if let Some(codec) = codec.encoder() {
                 let mut opts = options.disown();
                 let res = avcodec_open2(self.as_mut_ptr(), codec.as_ptr(), &mut opts);
 
-                Dictionary::own(opts);
+                let options = Dictionary::own(opts);
 
                 match res {
-                    0 => Ok(Encoder(self)),
+                    0 => Ok((Encoder(self), options)),
                     e => Err(Error::from(e)),
                 }
             } else {
@@ -459,6 +459,30 @@ impl Encoder {
         }
     }
 
+    #[inline]
+    pub fn send_frame(&mut self, frame: &frame::Video) -> Result<Status, Error> {
+        unsafe {
+            if self.format() != frame.format()
+                || self.width() != frame.width()
+                || self.height() != frame.height()
+            {
+                return Err(Error::InvalidData);
+            }
+
+            super::send_frame(self.0.as_mut_ptr(), frame.as_ptr())
+        }
+    }
+
+    #[inline]
+    pub fn send_eof(&mut self) -> Result<(), Error> {
+        unsafe { super::send_frame(self.0.as_mut_ptr(), ptr::null()).map(|_| ()) }
+    }
+
+    #[inline]
+    pub fn receive_packet<P: packet::Mut>(&mut self, out: &mut P) -> Result<Status, Error> {
+        unsafe { super::receive_packet(self.0.as_mut_ptr(), out.as_mut_ptr()) }
+    }
+
     #[inline]
     pub fn frame_size(&self) -> u32 {
         unsafe { (*self.as_ptr()).frame_size as u32 }
@@ -43,6 +43,13 @@ pub use self::profile::Profile;
 
 pub mod threading;
 
+pub mod status;
+pub use self::status::Status;
+
+pub mod parser;
+pub use self::parser::Parser;
+
+pub mod bsf;
 pub mod decoder;
 pub mod encoder;
 pub mod traits;
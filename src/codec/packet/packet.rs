@@ -196,11 +196,20 @@ impl Packet {
 
     #[inline]
     pub fn read(&mut self, format: &mut format::context::Input) -> Result<(), Error> {
+        if let Some(packet) = format.take_pending() {
+            *self = packet;
+            return Ok(());
+        }
+
         unsafe {
-            match av_read_frame(format.as_mut_ptr(), self.as_mut_ptr()) {
+            // drop the previous payload so the packet can be reused across reads
+            av_packet_unref(self.as_mut_ptr());
+
+            let ptr = format.as_mut_ptr();
+            format.guard(|| match av_read_frame(ptr, self.as_mut_ptr()) {
                 0 => Ok(()),
                 e => Err(Error::from(e)),
-            }
+            })
         }
     }
 
@@ -211,11 +220,12 @@ impl Packet {
                 return Err(Error::InvalidData);
             }
 
-            match av_write_frame(format.as_mut_ptr(), self.as_ptr() as *mut _) {
+            let ptr = format.as_mut_ptr();
+            format.guard(|| match av_write_frame(ptr, self.as_ptr() as *mut _) {
                 1 => Ok(true),
                 0 => Ok(false),
                 e => Err(Error::from(e)),
-            }
+            })
         }
     }
 
@@ -226,11 +236,14 @@ impl Packet {
                 return Err(Error::InvalidData);
             }
 
-            match av_interleaved_write_frame(format.as_mut_ptr(), self.as_ptr() as *mut _) {
-                1 => Ok(true),
-                0 => Ok(false),
-                e => Err(Error::from(e)),
-            }
+            let ptr = format.as_mut_ptr();
+            format.guard(
+                || match av_interleaved_write_frame(ptr, self.as_ptr() as *mut _) {
+                    1 => Ok(true),
+                    0 => Ok(false),
+                    e => Err(Error::from(e)),
+                },
+            )
         }
     }
 }
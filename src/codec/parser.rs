@@ -0,0 +1,207 @@
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use super::{Context, Id};
+use codec::packet::{flag, Mut};
+use ffi::*;
+use libc::c_int;
+use util::format;
+use {Error, Packet};
+
+pub struct Parser {
+    ptr: *mut AVCodecParserContext,
+    context: Context,
+}
+
+unsafe impl Send for Parser {}
+
+impl Parser {
+    pub unsafe fn as_ptr(&self) -> *const AVCodecParserContext {
+        self.ptr as *const _
+    }
+
+    pub unsafe fn as_mut_ptr(&mut self) -> *mut AVCodecParserContext {
+        self.ptr
+    }
+}
+
+impl Parser {
+    pub fn new(id: Id) -> Result<Self, Error> {
+        unsafe {
+            let codec: AVCodecID = id.into();
+            let ptr = av_parser_init(codec as c_int);
+
+            // not every codec with a decoder has a parser
+            if ptr.is_null() {
+                return Err(Error::InvalidArgument);
+            }
+
+            let mut context = Context::new();
+            (*context.as_mut_ptr()).codec_id = codec;
+
+            Ok(Parser {
+                ptr: ptr,
+                context: context,
+            })
+        }
+    }
+
+    // some parsers fill in the codec context as well (sample rate, channels,
+    // ...) which can then be copied to the decoder
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    // Returns how many bytes of `data` were consumed and whether `out` now
+    // holds a complete packet, the timestamps and position are the ones of
+    // the chunk `data` starts in.
+    pub fn parse_packet(
+        &mut self,
+        data: &[u8],
+        pts: Option<i64>,
+        dts: Option<i64>,
+        position: i64,
+        out: &mut Packet,
+    ) -> Result<(usize, bool), Error> {
+        unsafe {
+            let mut buffer = ptr::null_mut();
+            let mut size: c_int = 0;
+
+            let consumed = av_parser_parse2(
+                self.as_mut_ptr(),
+                self.context.as_mut_ptr(),
+                &mut buffer,
+                &mut size,
+                if data.is_empty() {
+                    ptr::null()
+                } else {
+                    data.as_ptr()
+                },
+                data.len() as c_int,
+                pts.unwrap_or(AV_NOPTS_VALUE),
+                dts.unwrap_or(AV_NOPTS_VALUE),
+                position,
+            );
+
+            if consumed < 0 {
+                return Err(Error::from(consumed));
+            }
+
+            if size == 0 {
+                return Ok((consumed as usize, false));
+            }
+
+            *out = Packet::copy(slice::from_raw_parts(buffer, size as usize));
+            out.set_pts(self.pts());
+            out.set_dts(self.dts());
+
+            if self.is_key() {
+                out.set_flags(flag::KEY);
+            }
+
+            let ptr = self.as_ptr();
+            (*out.as_mut_ptr()).pos = (*ptr).pos;
+
+            if (*ptr).duration > 0 {
+                (*out.as_mut_ptr()).duration = i64::from((*ptr).duration);
+            }
+
+            Ok((consumed as usize, true))
+        }
+    }
+
+    pub fn parse(
+        &mut self,
+        data: &[u8],
+        pts: Option<i64>,
+        dts: Option<i64>,
+        position: i64,
+    ) -> Result<Vec<Packet>, Error> {
+        let mut packets = Vec::new();
+        let mut data = data;
+
+        while !data.is_empty() {
+            let mut packet = Packet::empty();
+            let (consumed, complete) = self.parse_packet(data, pts, dts, position, &mut packet)?;
+
+            if complete {
+                packets.push(packet);
+            } else if consumed == 0 {
+                break;
+            }
+
+            data = &data[consumed..];
+        }
+
+        Ok(packets)
+    }
+
+    // the last packet is only output once the parser knows the stream ended
+    pub fn flush(&mut self) -> Result<Option<Packet>, Error> {
+        let mut packet = Packet::empty();
+
+        match self.parse_packet(&[], None, None, -1, &mut packet)? {
+            (_, true) => Ok(Some(packet)),
+            (_, false) => Ok(None),
+        }
+    }
+
+    pub fn pts(&self) -> Option<i64> {
+        unsafe {
+            match (*self.as_ptr()).pts {
+                AV_NOPTS_VALUE => None,
+                pts => Some(pts),
+            }
+        }
+    }
+
+    pub fn dts(&self) -> Option<i64> {
+        unsafe {
+            match (*self.as_ptr()).dts {
+                AV_NOPTS_VALUE => None,
+                dts => Some(dts),
+            }
+        }
+    }
+
+    pub fn is_key(&self) -> bool {
+        unsafe { (*self.as_ptr()).key_frame == 1 }
+    }
+
+    pub fn width(&self) -> u32 {
+        unsafe { (*self.as_ptr()).width as u32 }
+    }
+
+    pub fn height(&self) -> u32 {
+        unsafe { (*self.as_ptr()).height as u32 }
+    }
+
+    pub fn pixel_format(&self) -> format::Pixel {
+        unsafe {
+            if (*self.as_ptr()).format == -1 {
+                format::Pixel::None
+            } else {
+                format::Pixel::from(mem::transmute::<_, AVPixelFormat>((*self.as_ptr()).format))
+            }
+        }
+    }
+
+    pub fn sample_format(&self) -> format::Sample {
+        unsafe {
+            if (*self.as_ptr()).format == -1 {
+                format::Sample::None
+            } else {
+                format::Sample::from(mem::transmute::<_, AVSampleFormat>((*self.as_ptr()).format))
+            }
+        }
+    }
+}
+
+impl Drop for Parser {
+    fn drop(&mut self) {
+        unsafe {
+            av_parser_close(self.as_mut_ptr());
+        }
+    }
+}
@@ -0,0 +1,10 @@
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Status {
+    // the input was accepted or an output was produced
+    Ready,
+    // sending: the pending output has to be received first
+    // receiving: more input is needed before there is any output
+    Again,
+    // end of stream was signalled and every output has been received
+    Drained,
+}
@@ -0,0 +1,194 @@
+use std::ffi::CString;
+use std::path::Path;
+use std::ptr;
+
+use ffi::*;
+use format::{self, context, Io};
+use util::cancel::Cancellation;
+use util::interrupt::Interrupt;
+use {Dictionary, Error};
+
+enum Source {
+    Path(CString),
+    Io(Io),
+}
+
+pub struct InputBuilder<'a> {
+    source: Source,
+    format: Option<format::Input>,
+    options: Dictionary<'a>,
+    stream_options: Vec<(usize, Dictionary<'a>)>,
+    interrupt: Interrupt,
+    cancellation: Option<Cancellation>,
+    find_stream_info: bool,
+}
+
+impl<'a> InputBuilder<'a> {
+    pub fn new<P: AsRef<Path>>(path: &P) -> Self {
+        InputBuilder::with_source(Source::Path(format::from_path(path)))
+    }
+
+    pub fn with_io(io: Io) -> Self {
+        InputBuilder::with_source(Source::Io(io))
+    }
+
+    fn with_source(source: Source) -> Self {
+        InputBuilder {
+            source: source,
+            format: None,
+            options: Dictionary::new(),
+            stream_options: Vec::new(),
+            interrupt: Interrupt::empty(),
+            cancellation: None,
+            find_stream_info: true,
+        }
+    }
+
+    pub fn format(mut self, format: format::Input) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn options(mut self, options: Dictionary<'a>) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn stream_options(mut self, index: usize, options: Dictionary<'a>) -> Self {
+        self.stream_options.retain(|&(i, _)| i != index);
+        self.stream_options.push((index, options));
+        self
+    }
+
+    pub fn interrupt<F>(mut self, closure: F) -> Self
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        self.interrupt.set(closure);
+        self.cancellation = None;
+        self
+    }
+
+    pub fn cancellation(self, cancellation: &Cancellation) -> Self {
+        let interrupted = cancellation.clone();
+        let mut builder = self.interrupt(move || interrupted.is_interrupted());
+        builder.cancellation = Some(cancellation.clone());
+        builder
+    }
+
+    pub fn find_stream_info(mut self, value: bool) -> Self {
+        self.find_stream_info = value;
+        self
+    }
+
+    pub fn open(mut self) -> Result<(context::Input, Dictionary<'a>, Vec<Dictionary<'a>>), Error> {
+        match self.cancellation.take() {
+            Some(cancellation) => {
+                cancellation
+                    .run(|| self.open_context())
+                    .map(|(mut context, options, other)| {
+                        context.set_cancellation(&cancellation);
+                        (context, options, other)
+                    })
+            }
+
+            None => self.open_context(),
+        }
+    }
+
+    fn open_context(self) -> Result<(context::Input, Dictionary<'a>, Vec<Dictionary<'a>>), Error> {
+        let InputBuilder {
+            source,
+            mut format,
+            options,
+            mut stream_options,
+            interrupt,
+            find_stream_info,
+            ..
+        } = self;
+
+        unsafe {
+            let mut ps = avformat_alloc_context();
+
+            if ps.is_null() {
+                panic!("out of memory");
+            }
+
+            // always install the callback so it can be set after opening
+            (*ps).interrupt_callback = interrupt.interrupt;
+
+            let (path, io) = match source {
+                Source::Path(path) => (Some(path), None),
+
+                Source::Io(mut io) => {
+                    (*ps).pb = io.as_mut_ptr();
+                    (None, Some(io))
+                }
+            };
+
+            let mut opts = options.disown();
+            let res = avformat_open_input(
+                &mut ps,
+                path.as_ref().map(|p| p.as_ptr()).unwrap_or(ptr::null()),
+                format
+                    .as_mut()
+                    .map(|f| f.as_mut_ptr())
+                    .unwrap_or(ptr::null_mut()),
+                &mut opts,
+            );
+
+            let options = Dictionary::own(opts);
+
+            if res != 0 {
+                return Err(Error::from(res));
+            }
+
+            // the context is closed on drop if probing the streams fails
+            let context = context::Input::wrap_with(ps, io, Some(interrupt));
+
+            let nb_streams = (*ps).nb_streams as usize;
+
+            if stream_options.iter().any(|&(index, _)| index >= nb_streams) {
+                return Err(Error::StreamNotFound);
+            }
+
+            let stream_options = (0..nb_streams)
+                .map(
+                    |index| match stream_options.iter().position(|&(i, _)| i == index) {
+                        Some(position) => stream_options.swap_remove(position).1,
+                        None => Dictionary::new(),
+                    },
+                )
+                .collect::<Vec<_>>();
+
+            // nothing consumed the stream options, hand them back as they are
+            if !find_stream_info {
+                return Ok((context, options, stream_options));
+            }
+
+            let mut stream_opts = stream_options
+                .into_iter()
+                .map(|opts| opts.disown())
+                .collect::<Vec<_>>();
+
+            let res = avformat_find_stream_info(
+                ps,
+                if stream_opts.is_empty() {
+                    ptr::null_mut()
+                } else {
+                    stream_opts.as_mut_ptr()
+                },
+            );
+
+            let stream_options = stream_opts
+                .into_iter()
+                .map(|opts| Dictionary::own(opts))
+                .collect();
+
+            match res {
+                r if r >= 0 => Ok((context, options, stream_options)),
+                e => Err(Error::from(e)),
+            }
+        }
+    }
+}
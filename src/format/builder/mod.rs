@@ -0,0 +1,5 @@
+mod input;
+pub use self::input::InputBuilder;
+
+mod output;
+pub use self::output::OutputBuilder;
@@ -0,0 +1,166 @@
+use std::ffi::CString;
+use std::path::Path;
+use std::ptr;
+
+use ffi::*;
+use format::{self, context, Io};
+use libc::c_void;
+use util::cancel::Cancellation;
+use util::interrupt::Interrupt;
+use {Dictionary, Error};
+
+enum Target {
+    Path(CString),
+    Io(Io),
+}
+
+pub struct OutputBuilder<'a> {
+    target: Target,
+    format: Option<CString>,
+    options: Dictionary<'a>,
+    io_options: Dictionary<'a>,
+    interrupt: Interrupt,
+    cancellation: Option<Cancellation>,
+}
+
+impl<'a> OutputBuilder<'a> {
+    pub fn new<P: AsRef<Path>>(path: &P) -> Self {
+        OutputBuilder::with_target(Target::Path(format::from_path(path)))
+    }
+
+    pub fn with_io(io: Io) -> Self {
+        OutputBuilder::with_target(Target::Io(io))
+    }
+
+    fn with_target(target: Target) -> Self {
+        OutputBuilder {
+            target: target,
+            format: None,
+            options: Dictionary::new(),
+            io_options: Dictionary::new(),
+            interrupt: Interrupt::empty(),
+            cancellation: None,
+        }
+    }
+
+    pub fn format(mut self, name: &str) -> Self {
+        self.format = Some(CString::new(name).unwrap());
+        self
+    }
+
+    pub fn options(mut self, options: Dictionary<'a>) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn io_options(mut self, options: Dictionary<'a>) -> Self {
+        self.io_options = options;
+        self
+    }
+
+    pub fn interrupt<F>(mut self, closure: F) -> Self
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        self.interrupt.set(closure);
+        self.cancellation = None;
+        self
+    }
+
+    pub fn cancellation(self, cancellation: &Cancellation) -> Self {
+        let interrupted = cancellation.clone();
+        let mut builder = self.interrupt(move || interrupted.is_interrupted());
+        builder.cancellation = Some(cancellation.clone());
+        builder
+    }
+
+    pub fn open(mut self) -> Result<(context::Output, Dictionary<'a>, Dictionary<'a>), Error> {
+        match self.cancellation.take() {
+            Some(cancellation) => {
+                cancellation
+                    .run(|| self.open_context())
+                    .map(|(mut context, options, other)| {
+                        context.set_cancellation(&cancellation);
+                        (context, options, other)
+                    })
+            }
+
+            None => self.open_context(),
+        }
+    }
+
+    fn open_context(self) -> Result<(context::Output, Dictionary<'a>, Dictionary<'a>), Error> {
+        unsafe {
+            let mut ps = ptr::null_mut();
+            let path = match self.target {
+                Target::Path(ref path) => path.as_ptr(),
+                Target::Io(..) => ptr::null(),
+            };
+
+            match avformat_alloc_output_context2(
+                &mut ps,
+                ptr::null_mut(),
+                self.format
+                    .as_ref()
+                    .map(|f| f.as_ptr())
+                    .unwrap_or(ptr::null()),
+                path,
+            ) {
+                0 => (),
+                e => return Err(Error::from(e)),
+            }
+
+            // always install the callback so it can be set after opening
+            (*ps).interrupt_callback = self.interrupt.interrupt;
+
+            let mut opts = self.options.disown();
+            let res = av_opt_set_dict2(ps as *mut c_void, &mut opts, AV_OPT_SEARCH_CHILDREN);
+            let options = Dictionary::own(opts);
+
+            if res < 0 {
+                avformat_free_context(ps);
+                return Err(Error::from(res));
+            }
+
+            match self.target {
+                Target::Io(mut io) => {
+                    (*ps).pb = io.as_mut_ptr();
+                    (*ps).flags |= AVFMT_FLAG_CUSTOM_IO;
+
+                    Ok((
+                        context::Output::wrap_with(ps, Some(io), Some(self.interrupt)),
+                        options,
+                        self.io_options,
+                    ))
+                }
+
+                Target::Path(..) if (*(*ps).oformat).flags & AVFMT_NOFILE != 0 => Ok((
+                    context::Output::wrap_with(ps, None, Some(self.interrupt)),
+                    options,
+                    self.io_options,
+                )),
+
+                Target::Path(path) => {
+                    // the context is freed on drop if opening the file fails
+                    let context = context::Output::wrap_with(ps, None, Some(self.interrupt));
+
+                    let mut opts = self.io_options.disown();
+                    let res = avio_open2(
+                        &mut (*ps).pb,
+                        path.as_ptr(),
+                        AVIO_FLAG_WRITE,
+                        &(*ps).interrupt_callback,
+                        &mut opts,
+                    );
+
+                    let io_options = Dictionary::own(opts);
+
+                    match res {
+                        r if r >= 0 => Ok((context, options, io_options)),
+                        e => Err(Error::from(e)),
+                    }
+                }
+            }
+        }
+    }
+}
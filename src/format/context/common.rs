@@ -4,8 +4,11 @@ use std::rc::Rc;
 
 use super::destructor::{self, Destructor};
 use ffi::*;
+use format::Io;
 use libc::{c_int, c_uint};
-use {media, Chapter, ChapterMut, DictionaryRef, Stream, StreamMut};
+use util::cancel::Cancellation;
+use util::interrupt::Interrupt;
+use {media, Chapter, ChapterMut, DictionaryRef, Error, Program, ProgramMut, Stream, StreamMut};
 
 pub struct Context {
     ptr: *mut AVFormatContext,
@@ -22,6 +25,18 @@ impl Context {
         }
     }
 
+    pub unsafe fn wrap_with(
+        ptr: *mut AVFormatContext,
+        mode: destructor::Mode,
+        io: Option<Io>,
+        interrupt: Option<Interrupt>,
+    ) -> Self {
+        Context {
+            ptr: ptr,
+            dtor: Rc::new(Destructor::with(ptr, mode, io, interrupt)),
+        }
+    }
+
     pub unsafe fn as_ptr(&self) -> *const AVFormatContext {
         self.ptr as *const _
     }
@@ -112,9 +127,96 @@ impl Context {
         ChapterIterMut::new(self)
     }
 
+    pub fn nb_programs(&self) -> u32 {
+        unsafe { (*self.as_ptr()).nb_programs }
+    }
+
+    pub fn program<'a, 'b>(&'a self, index: usize) -> Option<Program<'b>>
+    where
+        'a: 'b,
+    {
+        unsafe {
+            if index >= (*self.as_ptr()).nb_programs as usize {
+                None
+            } else {
+                Some(Program::wrap(self, index))
+            }
+        }
+    }
+
+    pub fn program_mut<'a, 'b>(&'a mut self, index: usize) -> Option<ProgramMut<'b>>
+    where
+        'a: 'b,
+    {
+        unsafe {
+            if index >= (*self.as_ptr()).nb_programs as usize {
+                None
+            } else {
+                Some(ProgramMut::wrap(self, index))
+            }
+        }
+    }
+
+    pub fn programs(&self) -> ProgramIter {
+        ProgramIter::new(self)
+    }
+
+    pub fn programs_mut(&mut self) -> ProgramIterMut {
+        ProgramIterMut::new(self)
+    }
+
     pub fn metadata(&self) -> DictionaryRef {
         unsafe { DictionaryRef::wrap((*self.as_ptr()).metadata) }
     }
+
+    pub fn set_interrupt<F>(&mut self, closure: F)
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        *self.dtor.cancellation().borrow_mut() = None;
+        let mut interrupt = self.dtor.interrupt().borrow_mut();
+
+        if interrupt.is_none() {
+            let value = Interrupt::empty();
+
+            unsafe {
+                (*self.ptr).interrupt_callback = value.interrupt;
+            }
+
+            *interrupt = Some(value);
+        }
+
+        if let Some(ref mut interrupt) = *interrupt {
+            interrupt.set(closure);
+        }
+    }
+
+    pub fn set_cancellation(&mut self, cancellation: &Cancellation) {
+        let interrupted = cancellation.clone();
+        self.set_interrupt(move || interrupted.is_interrupted());
+        *self.dtor.cancellation().borrow_mut() = Some(cancellation.clone());
+    }
+
+    pub fn clear_interrupt(&mut self) {
+        *self.dtor.cancellation().borrow_mut() = None;
+
+        if let Some(ref mut interrupt) = *self.dtor.interrupt().borrow_mut() {
+            interrupt.clear();
+        }
+    }
+
+    // arms the cancellation deadline, if any, for a blocking call
+    pub fn guard<T, F>(&self, operation: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> Result<T, Error>,
+    {
+        let cancellation = self.dtor.cancellation().borrow().clone();
+
+        match cancellation {
+            Some(cancellation) => cancellation.run(operation),
+            None => operation(),
+        }
+    }
 }
 
 pub struct Best<'a> {
@@ -376,3 +478,92 @@ impl<'a> Iterator for ChapterIterMut<'a> {
 }
 
 impl<'a> ExactSizeIterator for ChapterIterMut<'a> {}
+
+pub struct ProgramIter<'a> {
+    context: &'a Context,
+    current: c_uint,
+}
+
+impl<'a> ProgramIter<'a> {
+    pub fn new<'s, 'c: 's>(context: &'c Context) -> ProgramIter<'s> {
+        ProgramIter {
+            context: context,
+            current: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for ProgramIter<'a> {
+    type Item = Program<'a>;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        unsafe {
+            if self.current >= (*self.context.as_ptr()).nb_programs {
+                return None;
+            }
+
+            self.current += 1;
+
+            Some(Program::wrap(self.context, (self.current - 1) as usize))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        unsafe {
+            let length = (*self.context.as_ptr()).nb_programs as usize;
+
+            (
+                length - self.current as usize,
+                Some(length - self.current as usize),
+            )
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for ProgramIter<'a> {}
+
+pub struct ProgramIterMut<'a> {
+    context: &'a mut Context,
+    current: c_uint,
+}
+
+impl<'a> ProgramIterMut<'a> {
+    pub fn new<'s, 'c: 's>(context: &'c mut Context) -> ProgramIterMut<'s> {
+        ProgramIterMut {
+            context: context,
+            current: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for ProgramIterMut<'a> {
+    type Item = ProgramMut<'a>;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        unsafe {
+            if self.current >= (*self.context.as_ptr()).nb_programs {
+                return None;
+            }
+
+            self.current += 1;
+
+            Some(ProgramMut::wrap(
+                mem::transmute_copy(&self.context),
+                (self.current - 1) as usize,
+            ))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        unsafe {
+            let length = (*self.context.as_ptr()).nb_programs as usize;
+
+            (
+                length - self.current as usize,
+                Some(length - self.current as usize),
+            )
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for ProgramIterMut<'a> {}
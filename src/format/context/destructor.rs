@@ -1,4 +1,9 @@
+use std::cell::RefCell;
+
 use ffi::*;
+use format::Io;
+use util::cancel::Cancellation;
+use util::interrupt::Interrupt;
 
 #[derive(Copy, Clone, Debug)]
 pub enum Mode {
@@ -9,6 +14,9 @@ pub enum Mode {
 pub struct Destructor {
     ptr: *mut AVFormatContext,
     mode: Mode,
+    io: Option<Io>,
+    interrupt: RefCell<Option<Interrupt>>,
+    cancellation: RefCell<Option<Cancellation>>,
 }
 
 impl Destructor {
@@ -16,8 +24,34 @@ impl Destructor {
         Destructor {
             ptr: ptr,
             mode: mode,
+            io: None,
+            interrupt: RefCell::new(None),
+            cancellation: RefCell::new(None),
+        }
+    }
+
+    pub unsafe fn with(
+        ptr: *mut AVFormatContext,
+        mode: Mode,
+        io: Option<Io>,
+        interrupt: Option<Interrupt>,
+    ) -> Self {
+        Destructor {
+            ptr: ptr,
+            mode: mode,
+            io: io,
+            interrupt: RefCell::new(interrupt),
+            cancellation: RefCell::new(None),
         }
     }
+
+    pub fn interrupt(&self) -> &RefCell<Option<Interrupt>> {
+        &self.interrupt
+    }
+
+    pub fn cancellation(&self) -> &RefCell<Option<Cancellation>> {
+        &self.cancellation
+    }
 }
 
 impl Drop for Destructor {
@@ -27,7 +61,11 @@ impl Drop for Destructor {
                 Mode::Input => avformat_close_input(&mut self.ptr),
 
                 Mode::Output => {
-                    avio_close((*self.ptr).pb);
+                    // custom I/O is released by `Io` once the context is gone
+                    if self.io.is_none() {
+                        avio_close((*self.ptr).pb);
+                    }
+
                     avformat_free_context(self.ptr);
                 }
             }
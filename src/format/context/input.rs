@@ -1,17 +1,26 @@
+use std::collections::VecDeque;
 use std::ffi::CString;
-use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::ptr;
+use std::time::Duration;
 
 use super::common::Context;
 use super::destructor;
+use codec::packet::Mut;
 use ffi::*;
+use format::{seek, Io};
+use libc::c_int;
+use util::interrupt::Interrupt;
 use util::range::Range;
-use {format, Codec, Error, Packet, Stream};
+use {format, rescale, Codec, Discard, Error, Packet, Rational, Rescale, Stream};
+
+// packets read past a seek while looking for where the demuxer landed
+const PEEK: usize = 64;
 
 pub struct Input {
     ptr: *mut AVFormatContext,
     ctx: Context,
+    pending: VecDeque<Packet>,
 }
 
 unsafe impl Send for Input {}
@@ -21,6 +30,19 @@ impl Input {
         Input {
             ptr: ptr,
             ctx: Context::wrap(ptr, destructor::Mode::Input),
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub unsafe fn wrap_with(
+        ptr: *mut AVFormatContext,
+        io: Option<Io>,
+        interrupt: Option<Interrupt>,
+    ) -> Self {
+        Input {
+            ptr: ptr,
+            ctx: Context::wrap_with(ptr, destructor::Mode::Input, io, interrupt),
+            pending: VecDeque::new(),
         }
     }
 
@@ -94,6 +116,48 @@ impl Input {
         PacketIter::new(self)
     }
 
+    pub fn read_packets(&mut self, recovery: Recovery) -> Packets {
+        Packets::new(self, recovery)
+    }
+
+    pub fn select_program(&mut self, id: i32) -> Result<(), Error> {
+        let streams = self
+            .programs()
+            .find(|p| p.id() == id)
+            .map(|p| p.stream_indices())
+            .ok_or(Error::InvalidArgument)?;
+
+        unsafe {
+            let ptr = self.as_mut_ptr();
+
+            for index in 0..(*ptr).nb_programs as isize {
+                let program = *(*ptr).programs.offset(index);
+
+                (*program).discard = if (*program).id == id {
+                    AVDiscard::AVDISCARD_DEFAULT
+                } else {
+                    AVDiscard::AVDISCARD_ALL
+                };
+            }
+        }
+
+        self.select_streams(&streams);
+
+        Ok(())
+    }
+
+    pub fn select_streams(&mut self, streams: &[usize]) {
+        for mut stream in self.streams_mut() {
+            let discard = if streams.contains(&stream.index()) {
+                Discard::Default
+            } else {
+                Discard::All
+            };
+
+            stream.set_discard(discard);
+        }
+    }
+
     pub fn pause(&mut self) -> Result<(), Error> {
         unsafe {
             match av_read_pause(self.as_mut_ptr()) {
@@ -113,19 +177,148 @@ impl Input {
     }
 
     pub fn seek<R: Range<i64>>(&mut self, ts: i64, range: R) -> Result<(), Error> {
+        self.pending.clear();
+
         unsafe {
-            match avformat_seek_file(
-                self.as_mut_ptr(),
-                -1,
-                range.start().cloned().unwrap_or(i64::min_value()),
-                ts,
-                range.end().cloned().unwrap_or(i64::max_value()),
-                0,
-            ) {
-                s if s >= 0 => Ok(()),
-                e => Err(Error::from(e)),
+            let ptr = self.as_mut_ptr();
+            self.guard(|| {
+                match avformat_seek_file(
+                    ptr,
+                    -1,
+                    range.start().cloned().unwrap_or(i64::min_value()),
+                    ts,
+                    range.end().cloned().unwrap_or(i64::max_value()),
+                    0,
+                ) {
+                    s if s >= 0 => Ok(()),
+                    e => Err(Error::from(e)),
+                }
+            })
+        }
+    }
+}
+
+impl Input {
+    pub fn seek_stream<R: Range<i64>>(
+        &mut self,
+        stream: Option<usize>,
+        ts: i64,
+        range: R,
+        flags: seek::Flags,
+    ) -> Result<seek::Landing, Error> {
+        unsafe {
+            let index = match stream {
+                Some(index) if index >= (*self.as_ptr()).nb_streams as usize => {
+                    return Err(Error::StreamNotFound)
+                }
+
+                Some(index) => index as c_int,
+                None => -1,
+            };
+
+            self.pending.clear();
+
+            // avformat_seek_file ignores the direction flag, express it as a range instead
+            let min = range.start().cloned().unwrap_or(i64::min_value());
+            let max = match range.end() {
+                Some(&end) => end,
+                None if flags.contains(seek::BACKWARD) => ts,
+                None => i64::max_value(),
+            };
+
+            let ptr = self.as_mut_ptr();
+            self.guard(|| {
+                match avformat_seek_file(ptr, index, min, ts, max, (flags - seek::BACKWARD).bits())
+                {
+                    s if s >= 0 => Ok(()),
+                    e => Err(Error::from(e)),
+                }
+            })?;
+
+            self.landing(stream)
+        }
+    }
+
+    pub fn seek_to(
+        &mut self,
+        stream: Option<usize>,
+        position: Duration,
+        flags: seek::Flags,
+    ) -> Result<seek::Landing, Error> {
+        self.seek_scaled(
+            stream,
+            rescale::from_duration(position),
+            rescale::TIME_BASE,
+            flags,
+        )
+    }
+
+    pub fn seek_scaled<T: Into<Rational>>(
+        &mut self,
+        stream: Option<usize>,
+        ts: i64,
+        time_base: T,
+        flags: seek::Flags,
+    ) -> Result<seek::Landing, Error> {
+        let destination = match stream {
+            Some(index) => self.stream(index).ok_or(Error::StreamNotFound)?.time_base(),
+            None => rescale::TIME_BASE,
+        };
+
+        self.seek_stream(stream, ts.rescale(time_base, destination), .., flags)
+    }
+
+    // next packet read ahead by a seek, handed out before reading again
+    pub(crate) fn take_pending(&mut self) -> Option<Packet> {
+        self.pending.pop_front()
+    }
+
+    // the demuxer may land anywhere around the target, so look at the first
+    // packet it returns for the stream and keep everything read until then
+    fn landing(&mut self, stream: Option<usize>) -> Result<seek::Landing, Error> {
+        let index = match stream {
+            Some(index) => index,
+            None => match unsafe { av_find_default_stream_index(self.as_mut_ptr()) } {
+                i if i < 0 => return Ok(seek::Landing::Unknown),
+                i => i as usize,
+            },
+        };
+
+        for _ in 0..PEEK {
+            let packet = match self.read_ahead() {
+                Ok(packet) => packet,
+                Err(Error::Eof) => break,
+                Err(e) => return Err(e),
+            };
+
+            let found = packet.stream() == index;
+            let key = packet.is_key();
+            self.pending.push_back(packet);
+
+            if found {
+                return Ok(if key {
+                    seek::Landing::Keyframe
+                } else {
+                    seek::Landing::NonKeyframe
+                });
             }
         }
+
+        Ok(seek::Landing::Unknown)
+    }
+
+    fn read_ahead(&mut self) -> Result<Packet, Error> {
+        let mut packet = Packet::empty();
+
+        unsafe {
+            let ptr = self.as_mut_ptr();
+            self.guard(|| match av_read_frame(ptr, packet.as_mut_ptr()) {
+                0 => Ok(()),
+                e => Err(Error::from(e)),
+            })?;
+        }
+
+        Ok(packet)
     }
 }
 
@@ -162,8 +355,10 @@ impl<'a> Iterator for PacketIter<'a> {
         loop {
             match packet.read(self.context) {
                 Ok(..) => unsafe {
+                    let context: &Context = self.context;
+
                     return Some((
-                        Stream::wrap(mem::transmute_copy(&self.context), packet.stream()),
+                        Stream::wrap(&*(context as *const Context), packet.stream()),
                         packet,
                     ));
                 },
@@ -176,6 +371,97 @@ impl<'a> Iterator for PacketIter<'a> {
     }
 }
 
+// `Skip` and `Retry` give up after that many consecutive errors
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Recovery {
+    Stop,
+    Skip(usize),
+    Retry(usize),
+}
+
+pub struct Packets<'a> {
+    context: &'a mut Input,
+    recovery: Recovery,
+    failures: usize,
+    done: bool,
+    spare: Option<Packet>,
+}
+
+impl<'a> Packets<'a> {
+    pub fn new(context: &mut Input, recovery: Recovery) -> Packets {
+        Packets {
+            context: context,
+            recovery: recovery,
+            failures: 0,
+            done: false,
+            spare: None,
+        }
+    }
+
+    // hands a yielded packet back so the next one is read into it
+    pub fn recycle(&mut self, packet: Packet) {
+        self.spare = Some(packet);
+    }
+
+    pub fn next_into(&mut self, packet: &mut Packet) -> Option<Result<Stream<'a>, Error>> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            match packet.read(self.context) {
+                Ok(..) => unsafe {
+                    let context: &Context = self.context;
+                    self.failures = 0;
+
+                    return Some(Ok(Stream::wrap(
+                        &*(context as *const Context),
+                        packet.stream(),
+                    )));
+                },
+
+                Err(Error::Eof) => {
+                    self.done = true;
+                    return None;
+                }
+
+                Err(e) => match self.recovery {
+                    Recovery::Retry(count) if self.failures < count => self.failures += 1,
+
+                    Recovery::Skip(count) if self.failures < count => {
+                        self.failures += 1;
+                        return Some(Err(e));
+                    }
+
+                    _ => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Packets<'a> {
+    type Item = Result<(Stream<'a>, Packet), Error>;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        let mut packet = self.spare.take().unwrap_or_else(Packet::empty);
+
+        match self.next_into(&mut packet) {
+            Some(Ok(stream)) => Some(Ok((stream, packet))),
+
+            Some(Err(e)) => {
+                self.spare = Some(packet);
+                Some(Err(e))
+            }
+
+            None => None,
+        }
+    }
+}
+
 pub fn dump(ctx: &Input, index: i32, url: Option<&str>) {
     let url = url.map(|u| CString::new(u).unwrap());
 
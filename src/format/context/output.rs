@@ -1,15 +1,17 @@
-use std::ffi::CString;
-use std::mem::size_of;
+use std::ffi::{CStr, CString};
+use std::mem::{self, size_of};
 use std::ops::{Deref, DerefMut};
 use std::ptr;
 
-use libc;
+use libc::{self, c_int};
 
 use super::common::Context;
 use super::destructor;
-use codec::traits;
+use codec::{self, packet, traits};
 use ffi::*;
-use {format, ChapterMut, Dictionary, Error, Rational, StreamMut};
+use format::Io;
+use util::interrupt::Interrupt;
+use {format, ChapterMut, Dictionary, Error, Packet, ProgramMut, Rational, StreamMut};
 
 pub struct Output {
     ptr: *mut AVFormatContext,
@@ -26,6 +28,17 @@ impl Output {
         }
     }
 
+    pub unsafe fn wrap_with(
+        ptr: *mut AVFormatContext,
+        io: Option<Io>,
+        interrupt: Option<Interrupt>,
+    ) -> Self {
+        Output {
+            ptr: ptr,
+            ctx: Context::wrap_with(ptr, destructor::Mode::Output, io, interrupt),
+        }
+    }
+
     pub unsafe fn as_ptr(&self) -> *const AVFormatContext {
         self.ptr as *const _
     }
@@ -42,31 +55,35 @@ impl Output {
 
     pub fn write_header(&mut self) -> Result<(), Error> {
         unsafe {
-            match avformat_write_header(self.as_mut_ptr(), ptr::null_mut()) {
+            let ptr = self.as_mut_ptr();
+            self.guard(|| match avformat_write_header(ptr, ptr::null_mut()) {
                 0 => Ok(()),
                 e => Err(Error::from(e)),
-            }
+            })
         }
     }
 
     pub fn write_header_with(&mut self, options: Dictionary) -> Result<Dictionary, Error> {
         unsafe {
             let mut opts = options.disown();
-            let res = avformat_write_header(self.as_mut_ptr(), &mut opts);
-
-            match res {
-                0 => Ok(Dictionary::own(opts)),
+            let ptr = self.as_mut_ptr();
+            let res = self.guard(|| match avformat_write_header(ptr, &mut opts) {
+                0 => Ok(()),
                 e => Err(Error::from(e)),
-            }
+            });
+
+            let opts = Dictionary::own(opts);
+            res.map(|_| opts)
         }
     }
 
     pub fn write_trailer(&mut self) -> Result<(), Error> {
         unsafe {
-            match av_write_trailer(self.as_mut_ptr()) {
+            let ptr = self.as_mut_ptr();
+            self.guard(|| match av_write_trailer(ptr) {
                 0 => Ok(()),
                 e => Err(Error::from(e)),
-            }
+            })
         }
     }
 
@@ -85,6 +102,137 @@ impl Output {
         }
     }
 
+    pub fn add_copy_stream<P: Into<codec::Parameters>>(
+        &mut self,
+        parameters: P,
+    ) -> Result<StreamMut, Error> {
+        let parameters = parameters.into();
+
+        unsafe {
+            // copy before creating the stream so a failure doesn't leave an
+            // empty one behind in the output
+            let mut copy = codec::Parameters::new();
+
+            match avcodec_parameters_copy(copy.as_mut_ptr(), parameters.as_ptr()) {
+                e if e < 0 => return Err(Error::from(e)),
+                _ => (),
+            }
+
+            let ptr = avformat_new_stream(self.as_mut_ptr(), ptr::null());
+
+            if ptr.is_null() {
+                panic!("out of memory");
+            }
+
+            // the stream takes the copy, the empty parameters go with `copy`
+            mem::swap(&mut *(*ptr).codecpar, &mut *copy.as_mut_ptr());
+
+            // same check ffmpeg does before keeping the input codec tag
+            let tags = (*(*self.as_ptr()).oformat).codec_tag;
+            let id = (*(*ptr).codecpar).codec_id;
+            let tag = (*(*ptr).codecpar).codec_tag;
+            let mut other = 0;
+
+            if !tags.is_null()
+                && av_codec_get_id(tags, tag) != id
+                && av_codec_get_tag2(tags, id, &mut other) != 0
+            {
+                (*(*ptr).codecpar).codec_tag = 0;
+            }
+
+            let index = (*self.ctx.as_ptr()).nb_streams - 1;
+
+            Ok(StreamMut::wrap(&mut self.ctx, index as usize))
+        }
+    }
+
+    pub fn add_attached_pic(
+        &mut self,
+        id: codec::Id,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<StreamMut, Error> {
+        unsafe {
+            let ptr = avformat_new_stream(self.as_mut_ptr(), ptr::null());
+
+            if ptr.is_null() {
+                panic!("out of memory");
+            }
+
+            let (mime, name) = match id {
+                codec::Id::PNG => ("image/png", "cover.png"),
+                codec::Id::MJPEG => ("image/jpeg", "cover.jpg"),
+                _ => ("application/octet-stream", "cover"),
+            };
+
+            let name = CString::new(name).unwrap();
+            let oformat = CStr::from_ptr((*(*self.as_ptr()).oformat).name);
+            let par = (*ptr).codecpar;
+            (*par).codec_id = id.into();
+
+            // matroska stores pictures as attachments rather than as a stream
+            if oformat.to_bytes().starts_with(b"matroska") {
+                let extradata = av_mallocz(data.len() + AV_INPUT_BUFFER_PADDING_SIZE as usize);
+
+                if extradata.is_null() {
+                    panic!("out of memory");
+                }
+
+                ptr::copy_nonoverlapping(data.as_ptr(), extradata as *mut u8, data.len());
+
+                (*par).codec_type = AVMediaType::AVMEDIA_TYPE_ATTACHMENT;
+                (*par).extradata = extradata as *mut u8;
+                (*par).extradata_size = data.len() as c_int;
+
+                let mime = CString::new(mime).unwrap();
+                let key = CString::new("mimetype").unwrap();
+                av_dict_set(&mut (*ptr).metadata, key.as_ptr(), mime.as_ptr(), 0);
+
+                let key = CString::new("filename").unwrap();
+                av_dict_set(&mut (*ptr).metadata, key.as_ptr(), name.as_ptr(), 0);
+            } else {
+                (*par).codec_type = AVMediaType::AVMEDIA_TYPE_VIDEO;
+                (*par).width = width as c_int;
+                (*par).height = height as c_int;
+                (*ptr).disposition |= AV_DISPOSITION_ATTACHED_PIC;
+
+                // the packet is freed along with the stream, `write_attached_pics`
+                // sends it to the muxer
+                match av_new_packet(&mut (*ptr).attached_pic, data.len() as c_int) {
+                    e if e < 0 => return Err(Error::from(e)),
+                    _ => (),
+                }
+
+                ptr::copy_nonoverlapping(data.as_ptr(), (*ptr).attached_pic.data, data.len());
+                (*ptr).attached_pic.stream_index = (*ptr).index;
+                (*ptr).attached_pic.flags |= AV_PKT_FLAG_KEY;
+            }
+
+            let index = (*self.ctx.as_ptr()).nb_streams - 1;
+
+            Ok(StreamMut::wrap(&mut self.ctx, index as usize))
+        }
+    }
+
+    pub fn add_program(&mut self, id: i32) -> Result<ProgramMut, Error> {
+        unsafe {
+            // returns the existing program if one already has this id
+            let ptr = av_new_program(self.as_mut_ptr(), id);
+
+            if ptr.is_null() {
+                panic!("out of memory");
+            }
+
+            let index = self
+                .programs()
+                .position(|p| p.as_ptr() == ptr as *const _)
+                .ok_or(Error::Bug)?;
+
+            Ok(ProgramMut::wrap(&mut self.ctx, index))
+        }
+    }
+
     pub fn add_chapter<R: Into<Rational>, S: AsRef<str>>(
         &mut self,
         id: i32,
@@ -152,6 +300,27 @@ impl Output {
     }
 }
 
+impl Output {
+    // muxers expect the pictures right after the header and before any other
+    // packet, `write_header` leaves that to the caller
+    pub fn write_attached_pics(&mut self) -> Result<(), Error> {
+        let pictures = self
+            .streams()
+            .filter_map(|s| s.attached_pic().map(|data| (s.index(), Packet::copy(data))))
+            .collect::<Vec<_>>();
+
+        for (index, mut packet) in pictures {
+            packet.set_stream(index);
+            packet.set_flags(packet::flag::KEY);
+            packet.set_pts(Some(0));
+            packet.set_dts(Some(0));
+            packet.write(self)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Deref for Output {
     type Target = Context;
 
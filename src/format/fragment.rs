@@ -0,0 +1,215 @@
+use std::io::{self, Write};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ffi::*;
+use format::{context, Io, OutputBuilder};
+use {media, rescale, Dictionary, Error, Packet, Rescale};
+
+const FLAGS: &str = "frag_custom+empty_moov+default_base_moof";
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Cut {
+    Keyframe,
+    Every(Duration),
+    Manual,
+}
+
+struct Sink(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Fragmented mp4 output, the muxer only cuts a fragment when asked to so
+// every moof+mdat pair is handed to the fragment callback as soon as it is
+// flushed. Packets are written as is, so they must already be interleaved.
+pub struct Fragmenter<'a> {
+    output: context::Output,
+    buffer: Arc<Mutex<Vec<u8>>>,
+    cut: Cut,
+    start: Option<i64>,
+    pending: bool,
+
+    init: Option<Box<dyn FnMut(&[u8]) + 'a>>,
+    fragment: Option<Box<dyn FnMut(&[u8]) + 'a>>,
+}
+
+impl<'a> Fragmenter<'a> {
+    pub fn new(cut: Cut) -> Result<Self, Error> {
+        Fragmenter::with_format("mp4", cut)
+    }
+
+    pub fn with_format(format: &str, cut: Cut) -> Result<Self, Error> {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let (output, _, _) = OutputBuilder::with_io(Io::writer(Sink(buffer.clone())))
+            .format(format)
+            .open()?;
+
+        Ok(Fragmenter {
+            output: output,
+            buffer: buffer,
+            cut: cut,
+            start: None,
+            pending: false,
+
+            init: None,
+            fragment: None,
+        })
+    }
+
+    pub fn on_init<F>(mut self, closure: F) -> Self
+    where
+        F: FnMut(&[u8]) + 'a,
+    {
+        self.init = Some(Box::new(closure));
+        self
+    }
+
+    pub fn on_fragment<F>(mut self, closure: F) -> Self
+    where
+        F: FnMut(&[u8]) + 'a,
+    {
+        self.fragment = Some(Box::new(closure));
+        self
+    }
+
+    pub fn write_header(&mut self) -> Result<(), Error> {
+        self.write_header_with(Dictionary::new()).map(|_| ())
+    }
+
+    pub fn write_header_with(&mut self, options: Dictionary) -> Result<Dictionary, Error> {
+        let mut options = options;
+        let flags = match options.iter().find(|&(key, _)| key == "movflags") {
+            Some((_, flags)) => format!("{}+{}", flags, FLAGS),
+            None => FLAGS.to_owned(),
+        };
+        options.set("movflags", &flags);
+
+        let options = self.output.write_header_with(options)?;
+
+        // with an empty moov the header is the whole init segment
+        let init = self.take();
+        if let Some(ref mut callback) = self.init {
+            callback(&init);
+        }
+
+        Ok(options)
+    }
+
+    pub fn write(&mut self, packet: &Packet) -> Result<(), Error> {
+        let reference = self.reference();
+
+        if packet.stream() == reference {
+            let time_base = self
+                .output
+                .stream(reference)
+                .ok_or(Error::StreamNotFound)?
+                .time_base();
+            let pts = packet
+                .pts()
+                .or_else(|| packet.dts())
+                .map(|v| v.rescale(time_base, rescale::TIME_BASE));
+
+            let cut = match self.cut {
+                Cut::Keyframe => packet.is_key(),
+                // only cut where the next fragment can be decoded on its own
+                Cut::Every(duration) => match (self.start, pts) {
+                    (Some(start), Some(pts)) if packet.is_key() => {
+                        pts - start >= rescale::from_duration(duration)
+                    }
+
+                    _ => false,
+                },
+                Cut::Manual => false,
+            };
+
+            if cut {
+                self.flush()?;
+            }
+
+            if self.start.is_none() {
+                self.start = pts;
+            }
+        }
+
+        packet.write(&mut self.output)?;
+        self.pending = true;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if !self.pending {
+            return Ok(());
+        }
+
+        unsafe {
+            match av_write_frame(self.output.as_mut_ptr(), ptr::null_mut()) {
+                e if e < 0 => return Err(Error::from(e)),
+                _ => (),
+            }
+        }
+
+        self.pending = false;
+        self.start = None;
+
+        let fragment = self.take();
+        if let Some(ref mut callback) = self.fragment {
+            callback(&fragment);
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<(), Error> {
+        self.flush()?;
+        self.output.write_trailer()?;
+
+        // the trailer only holds the fragment index, which is of no use to
+        // streaming clients
+        self.take();
+
+        Ok(())
+    }
+
+    fn reference(&self) -> usize {
+        self.output
+            .streams()
+            .find(|s| s.parameters().medium() == media::Type::Video)
+            .map(|s| s.index())
+            .unwrap_or(0)
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        unsafe {
+            avio_flush((*self.output.as_mut_ptr()).pb);
+        }
+
+        mem::replace(&mut *self.buffer.lock().unwrap(), Vec::new())
+    }
+}
+
+impl<'a> Deref for Fragmenter<'a> {
+    type Target = context::Output;
+
+    fn deref(&self) -> &Self::Target {
+        &self.output
+    }
+}
+
+impl<'a> DerefMut for Fragmenter<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.output
+    }
+}
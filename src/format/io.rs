@@ -0,0 +1,235 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::panic;
+use std::process;
+use std::slice;
+
+use ffi::*;
+use libc::{c_int, c_void, EINVAL, EIO, SEEK_CUR, SEEK_END, SEEK_SET};
+
+const BUFFER_SIZE: usize = 32 * 1024;
+
+// Custom AVIOContext backed by a Rust reader or writer, the boxed backend is
+// passed to the callbacks as the opaque pointer and freed with the context.
+pub struct Io {
+    ptr: *mut AVIOContext,
+    opaque: *mut c_void,
+    free: unsafe fn(*mut c_void),
+}
+
+unsafe impl Send for Io {}
+
+impl Io {
+    pub unsafe fn as_ptr(&self) -> *const AVIOContext {
+        self.ptr as *const _
+    }
+
+    pub unsafe fn as_mut_ptr(&mut self) -> *mut AVIOContext {
+        self.ptr
+    }
+}
+
+impl Io {
+    pub fn reader<R: Read + Send + 'static>(reader: R) -> Self {
+        unsafe {
+            let buffer = buffer();
+            let opaque = Box::into_raw(Box::new(reader)) as *mut c_void;
+            let ptr = avio_alloc_context(
+                buffer,
+                BUFFER_SIZE as c_int,
+                0,
+                opaque,
+                Some(read::<R>),
+                None,
+                None,
+            );
+
+            Io::wrap::<R>(ptr, buffer, opaque)
+        }
+    }
+
+    pub fn seekable_reader<R: Read + Seek + Send + 'static>(reader: R) -> Self {
+        unsafe {
+            let buffer = buffer();
+            let opaque = Box::into_raw(Box::new(reader)) as *mut c_void;
+            let ptr = avio_alloc_context(
+                buffer,
+                BUFFER_SIZE as c_int,
+                0,
+                opaque,
+                Some(read::<R>),
+                None,
+                Some(seek::<R>),
+            );
+
+            Io::wrap::<R>(ptr, buffer, opaque)
+        }
+    }
+
+    pub fn writer<W: Write + Send + 'static>(writer: W) -> Self {
+        unsafe {
+            let buffer = buffer();
+            let opaque = Box::into_raw(Box::new(writer)) as *mut c_void;
+            let ptr = avio_alloc_context(
+                buffer,
+                BUFFER_SIZE as c_int,
+                1,
+                opaque,
+                None,
+                Some(write::<W>),
+                None,
+            );
+
+            Io::wrap::<W>(ptr, buffer, opaque)
+        }
+    }
+
+    pub fn seekable_writer<W: Write + Seek + Send + 'static>(writer: W) -> Self {
+        unsafe {
+            let buffer = buffer();
+            let opaque = Box::into_raw(Box::new(writer)) as *mut c_void;
+            let ptr = avio_alloc_context(
+                buffer,
+                BUFFER_SIZE as c_int,
+                1,
+                opaque,
+                None,
+                Some(write::<W>),
+                Some(seek::<W>),
+            );
+
+            Io::wrap::<W>(ptr, buffer, opaque)
+        }
+    }
+
+    pub fn is_seekable(&self) -> bool {
+        unsafe { (*self.as_ptr()).seekable & AVIO_SEEKABLE_NORMAL != 0 }
+    }
+
+    unsafe fn wrap<T>(ptr: *mut AVIOContext, buffer: *mut u8, opaque: *mut c_void) -> Self {
+        if ptr.is_null() {
+            av_free(buffer as *mut c_void);
+            free::<T>(opaque);
+
+            panic!("out of memory");
+        }
+
+        Io {
+            ptr: ptr,
+            opaque: opaque,
+            free: free::<T>,
+        }
+    }
+}
+
+impl Drop for Io {
+    fn drop(&mut self) {
+        unsafe {
+            if (*self.ptr).write_flag != 0 {
+                avio_flush(self.ptr);
+            }
+
+            // the buffer may have been reallocated by libavformat, so free the
+            // current one rather than the one we allocated
+            av_freep(&mut (*self.ptr).buffer as *mut _ as *mut c_void);
+            avio_context_free(&mut self.ptr);
+
+            (self.free)(self.opaque);
+        }
+    }
+}
+
+unsafe fn buffer() -> *mut u8 {
+    let buffer = av_malloc(BUFFER_SIZE) as *mut u8;
+
+    if buffer.is_null() {
+        panic!("out of memory");
+    }
+
+    buffer
+}
+
+unsafe fn free<T>(opaque: *mut c_void) {
+    drop(Box::from_raw(opaque as *mut T));
+}
+
+fn error(error: &io::Error) -> c_int {
+    match error.kind() {
+        io::ErrorKind::UnexpectedEof => AVERROR_EOF,
+        _ => AVERROR(error.raw_os_error().unwrap_or(EIO)),
+    }
+}
+
+unsafe extern "C" fn read<R: Read>(opaque: *mut c_void, buf: *mut u8, size: c_int) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let reader = &mut *(opaque as *mut R);
+        let buf = slice::from_raw_parts_mut(buf, size as usize);
+
+        loop {
+            match reader.read(buf) {
+                Ok(0) => return AVERROR_EOF,
+                Ok(n) => return n as c_int,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(ref e) => return error(e),
+            }
+        }
+    });
+
+    match result {
+        Ok(ret) => ret,
+        Err(_) => process::abort(),
+    }
+}
+
+unsafe extern "C" fn write<W: Write>(opaque: *mut c_void, buf: *mut u8, size: c_int) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let writer = &mut *(opaque as *mut W);
+        let buf = slice::from_raw_parts(buf as *const u8, size as usize);
+
+        match writer.write_all(buf).and_then(|_| writer.flush()) {
+            Ok(..) => size,
+            Err(ref e) => error(e),
+        }
+    });
+
+    match result {
+        Ok(ret) => ret,
+        Err(_) => process::abort(),
+    }
+}
+
+unsafe extern "C" fn seek<S: Seek>(opaque: *mut c_void, offset: i64, whence: c_int) -> i64 {
+    let result = panic::catch_unwind(|| {
+        let stream = &mut *(opaque as *mut S);
+        let position = match whence & !AVSEEK_FORCE {
+            AVSEEK_SIZE => return size(stream),
+            SEEK_SET => SeekFrom::Start(offset as u64),
+            SEEK_CUR => SeekFrom::Current(offset),
+            SEEK_END => SeekFrom::End(offset),
+            _ => return i64::from(AVERROR(EINVAL)),
+        };
+
+        match stream.seek(position) {
+            Ok(position) => position as i64,
+            Err(ref e) => i64::from(error(e)),
+        }
+    });
+
+    match result {
+        Ok(ret) => ret,
+        Err(_) => process::abort(),
+    }
+}
+
+fn size<S: Seek>(stream: &mut S) -> i64 {
+    let result = stream.seek(SeekFrom::Current(0)).and_then(|current| {
+        let end = stream.seek(SeekFrom::End(0))?;
+        stream.seek(SeekFrom::Start(current))?;
+
+        Ok(end)
+    });
+
+    match result {
+        Ok(size) => size as i64,
+        Err(ref e) => i64::from(error(e)),
+    }
+}
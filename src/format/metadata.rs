@@ -0,0 +1,278 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ffi::*;
+use format::context::common::Context;
+use format::context::Output;
+use {Dictionary, Error, Rational};
+
+const HEADER: &str = ";FFMETADATA1";
+
+type Tags = Vec<(String, String)>;
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Chapter {
+    pub time_base: Rational,
+    pub start: i64,
+    pub end: i64,
+    pub metadata: Tags,
+}
+
+impl Default for Chapter {
+    fn default() -> Self {
+        Chapter {
+            // same default as the ffmetadata demuxer
+            time_base: Rational(1, 1_000_000_000),
+            start: 0,
+            end: 0,
+            metadata: Vec::new(),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Default, Debug)]
+pub struct Metadata {
+    pub global: Tags,
+    pub streams: Vec<Tags>,
+    pub chapters: Vec<Chapter>,
+}
+
+enum Section {
+    Global,
+    Stream,
+    Chapter,
+}
+
+impl Metadata {
+    pub fn new() -> Self {
+        Metadata::default()
+    }
+
+    pub fn from_context(context: &Context) -> Self {
+        Metadata {
+            global: tags(context.metadata().iter()),
+            streams: context
+                .streams()
+                .map(|s| tags(s.metadata().iter()))
+                .collect(),
+            chapters: context
+                .chapters()
+                .map(|c| Chapter {
+                    time_base: c.time_base(),
+                    start: c.start(),
+                    end: c.end(),
+                    metadata: tags(c.metadata().iter()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn apply(&self, output: &mut Output) -> Result<(), Error> {
+        output.set_metadata(dictionary(&self.global));
+
+        for (index, metadata) in self.streams.iter().enumerate() {
+            if let Some(mut stream) = output.stream_mut(index) {
+                stream.set_metadata(dictionary(metadata));
+            }
+        }
+
+        // chapters are replaced like the tags, same cleanup as
+        // avformat_free_context
+        unsafe {
+            let ptr = output.as_mut_ptr();
+
+            for index in 0..(*ptr).nb_chapters as isize {
+                let chapter = (*ptr).chapters.offset(index);
+
+                av_dict_free(&mut (**chapter).metadata);
+                av_freep(chapter as *mut _);
+            }
+
+            av_freep(&mut (*ptr).chapters as *mut _ as *mut _);
+            (*ptr).nb_chapters = 0;
+        }
+
+        for (index, chapter) in self.chapters.iter().enumerate() {
+            let mut added = output.add_chapter(
+                index as i32,
+                chapter.time_base,
+                chapter.start,
+                chapter.end,
+                "",
+            )?;
+
+            // drop the title set by add_chapter, it may not be in the tags
+            unsafe {
+                av_dict_free(&mut (*added.as_mut_ptr()).metadata);
+            }
+
+            for &(ref key, ref value) in &chapter.metadata {
+                added.set_metadata(key, value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Metadata {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if !s.starts_with(HEADER) {
+            return Err(Error::InvalidData);
+        }
+
+        let mut metadata = Metadata::new();
+        let mut section = Section::Global;
+
+        for line in lines(&s[HEADER.len()..]) {
+            match line.first() {
+                None => continue,
+                Some(&(';', false)) | Some(&('#', false)) => continue,
+                _ => (),
+            }
+
+            let text = line.iter().map(|&(c, _)| c).collect::<String>();
+
+            if text == "[STREAM]" {
+                metadata.streams.push(Vec::new());
+                section = Section::Stream;
+                continue;
+            }
+
+            if text == "[CHAPTER]" {
+                metadata.chapters.push(Chapter::default());
+                section = Section::Chapter;
+                continue;
+            }
+
+            // the ffmetadata demuxer ignores lines that are not key=value
+            let split = match line.iter().position(|&(c, escaped)| c == '=' && !escaped) {
+                Some(split) => split,
+                None => continue,
+            };
+            let key = line[..split].iter().map(|&(c, _)| c).collect::<String>();
+            let value = line[split + 1..]
+                .iter()
+                .map(|&(c, _)| c)
+                .collect::<String>();
+
+            match section {
+                Section::Global => metadata.global.push((key, value)),
+                Section::Stream => metadata.streams.last_mut().unwrap().push((key, value)),
+                Section::Chapter => {
+                    let chapter = metadata.chapters.last_mut().unwrap();
+
+                    match &key[..] {
+                        "TIMEBASE" => chapter.time_base = time_base(&value)?,
+                        "START" => chapter.start = timestamp(&value)?,
+                        "END" => chapter.end = timestamp(&value)?,
+                        _ => chapter.metadata.push((key, value)),
+                    }
+                }
+            }
+        }
+
+        Ok(metadata)
+    }
+}
+
+impl fmt::Display for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        write_tags(f, &self.global)?;
+
+        for stream in &self.streams {
+            writeln!(f, "[STREAM]")?;
+            write_tags(f, stream)?;
+        }
+
+        for chapter in &self.chapters {
+            writeln!(f, "[CHAPTER]")?;
+            writeln!(
+                f,
+                "TIMEBASE={}/{}",
+                chapter.time_base.numerator(),
+                chapter.time_base.denominator()
+            )?;
+            writeln!(f, "START={}", chapter.start)?;
+            writeln!(f, "END={}", chapter.end)?;
+            write_tags(f, &chapter.metadata)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn tags<'a, I: Iterator<Item = (&'a str, &'a str)>>(iter: I) -> Tags {
+    iter.map(|(k, v)| (k.to_owned(), v.to_owned())).collect()
+}
+
+fn dictionary<'a>(tags: &Tags) -> Dictionary<'a> {
+    tags.iter().collect()
+}
+
+fn time_base(value: &str) -> Result<Rational, Error> {
+    let mut parts = value.splitn(2, '/');
+
+    match (
+        parts.next().and_then(|n| n.parse().ok()),
+        parts.next().and_then(|d| d.parse().ok()),
+    ) {
+        (Some(num), Some(den)) if den != 0 => Ok(Rational(num, den)),
+        _ => Err(Error::InvalidData),
+    }
+}
+
+fn timestamp(value: &str) -> Result<i64, Error> {
+    value.parse().map_err(|_| Error::InvalidData)
+}
+
+// splits on unescaped newlines, keeping track of which characters were
+// escaped so `=`, `;` and `#` can be told apart from literal ones
+fn lines(s: &str) -> Vec<Vec<(char, bool)>> {
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    line.push((c, true));
+                }
+            }
+
+            '\n' => lines.push(line.split_off(0)),
+            '\r' => (),
+            c => line.push((c, false)),
+        }
+    }
+
+    lines.push(line);
+    lines
+}
+
+fn write_tags(f: &mut fmt::Formatter, tags: &Tags) -> fmt::Result {
+    for &(ref key, ref value) in tags {
+        writeln!(f, "{}={}", escape(key), escape(value))?;
+    }
+
+    Ok(())
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '=' | ';' | '#' | '\\' | '\n' => escaped.push('\\'),
+            _ => (),
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
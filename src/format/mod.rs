@@ -1,11 +1,15 @@
 pub use util::format::{pixel, Pixel};
 pub use util::format::{sample, Sample};
-use util::interrupt;
 
 pub mod stream;
 
 pub mod chapter;
 
+pub mod program;
+
+pub mod metadata;
+pub use self::metadata::Metadata;
+
 pub mod context;
 pub use self::context::Context;
 
@@ -15,12 +19,31 @@ pub use self::format::{list, Input, Output};
 
 pub mod network;
 
+pub mod seek;
+
+pub mod remux;
+pub use self::remux::{remux, remux_all};
+
+pub mod segment;
+pub use self::segment::Segmenter;
+
+pub mod fragment;
+pub use self::fragment::Fragmenter;
+
+pub mod io;
+pub use self::io::Io;
+
+pub mod builder;
+pub use self::builder::{InputBuilder, OutputBuilder};
+
 use std::ffi::{CStr, CString};
+use std::mem;
 use std::path::Path;
 use std::ptr;
 use std::str::from_utf8_unchecked;
 
 use ffi::*;
+use libc::c_int;
 use {Dictionary, Error, Format};
 
 pub fn register_all() {
@@ -53,6 +76,50 @@ pub fn license() -> &'static str {
     unsafe { from_utf8_unchecked(CStr::from_ptr(avformat_license()).to_bytes()) }
 }
 
+pub fn probe(data: &[u8], filename: Option<&str>) -> Option<(Input, i32)> {
+    unsafe {
+        let filename = CString::new(filename.unwrap_or("")).unwrap();
+
+        // probe buffers must be followed by zeroed padding
+        let mut buffer = Vec::with_capacity(data.len() + AVPROBE_PADDING_SIZE as usize);
+        buffer.extend_from_slice(data);
+        buffer.resize(data.len() + AVPROBE_PADDING_SIZE as usize, 0u8);
+
+        let mut pd: AVProbeData = mem::zeroed();
+        pd.filename = filename.as_ptr();
+        pd.buf = buffer.as_mut_ptr();
+        pd.buf_size = data.len() as c_int;
+
+        let mut score: c_int = 0;
+        let ptr = av_probe_input_format3(&mut pd, 1, &mut score);
+
+        if ptr.is_null() {
+            None
+        } else {
+            Some((Input::wrap(ptr as *mut _), score as i32))
+        }
+    }
+}
+
+pub fn probe_from(io: &mut Io, filename: Option<&str>) -> Result<(Input, i32), Error> {
+    unsafe {
+        let filename = CString::new(filename.unwrap_or("")).unwrap();
+        let mut ptr = ptr::null_mut();
+
+        match av_probe_input_buffer2(
+            io.as_mut_ptr(),
+            &mut ptr,
+            filename.as_ptr(),
+            ptr::null_mut(),
+            0,
+            0,
+        ) {
+            s if s >= 0 => Ok((Input::wrap(ptr as *mut _), s as i32)),
+            e => Err(Error::from(e)),
+        }
+    }
+}
+
 // XXX: use to_cstring when stable
 fn from_path<P: AsRef<Path>>(path: &P) -> CString {
     CString::new(path.as_ref().as_os_str().to_str().unwrap()).unwrap()
@@ -60,133 +127,45 @@ fn from_path<P: AsRef<Path>>(path: &P) -> CString {
 
 // NOTE: this will be better with specialization or anonymous return types
 pub fn open<P: AsRef<Path>>(path: &P, format: &Format) -> Result<Context, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-
-        match *format {
-            Format::Input(ref format) => match avformat_open_input(
-                &mut ps,
-                path.as_ptr(),
-                format.as_ptr() as *mut _,
-                ptr::null_mut(),
-            ) {
-                0 => match avformat_find_stream_info(ps, ptr::null_mut()) {
-                    r if r >= 0 => Ok(Context::Input(context::Input::wrap(ps))),
-                    e => Err(Error::from(e)),
-                },
-
-                e => Err(Error::from(e)),
-            },
-
-            Format::Output(ref format) => match avformat_alloc_output_context2(
-                &mut ps,
-                format.as_ptr() as *mut _,
-                ptr::null(),
-                path.as_ptr(),
-            ) {
-                0 => match avio_open(&mut (*ps).pb, path.as_ptr(), AVIO_FLAG_WRITE) {
-                    0 => Ok(Context::Output(context::Output::wrap(ps))),
-                    e => Err(Error::from(e)),
-                },
-
-                e => Err(Error::from(e)),
-            },
-        }
-    }
+    open_with(path, format, Dictionary::new()).map(|(context, _)| context)
 }
 
-pub fn open_with<P: AsRef<Path>>(
+// goes through the builders so the interrupt slot is installed before the
+// context is opened and `set_interrupt` reaches the protocol contexts
+pub fn open_with<'a, P: AsRef<Path>>(
     path: &P,
     format: &Format,
-    options: Dictionary,
-) -> Result<Context, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-        let mut opts = options.disown();
-
-        match *format {
-            Format::Input(ref format) => {
-                let res = avformat_open_input(
-                    &mut ps,
-                    path.as_ptr(),
-                    format.as_ptr() as *mut _,
-                    &mut opts,
-                );
-
-                Dictionary::own(opts);
-
-                match res {
-                    0 => match avformat_find_stream_info(ps, ptr::null_mut()) {
-                        r if r >= 0 => Ok(Context::Input(context::Input::wrap(ps))),
-                        e => Err(Error::from(e)),
-                    },
-
-                    e => Err(Error::from(e)),
-                }
-            }
-
-            Format::Output(ref format) => match avformat_alloc_output_context2(
-                &mut ps,
-                format.as_ptr() as *mut _,
-                ptr::null(),
-                path.as_ptr(),
-            ) {
-                0 => match avio_open(&mut (*ps).pb, path.as_ptr(), AVIO_FLAG_WRITE) {
-                    0 => Ok(Context::Output(context::Output::wrap(ps))),
-                    e => Err(Error::from(e)),
-                },
-
-                e => Err(Error::from(e)),
-            },
-        }
+    options: Dictionary<'a>,
+) -> Result<(Context, Dictionary<'a>), Error> {
+    match *format {
+        Format::Input(ref format) => InputBuilder::new(path)
+            .format(unsafe { Input::wrap(format.as_ptr() as *mut _) })
+            .options(options)
+            .open()
+            .map(|(context, options, _)| (Context::Input(context), options)),
+
+        Format::Output(ref format) => OutputBuilder::new(path)
+            .format(format.name())
+            .io_options(options)
+            .open()
+            .map(|(context, _, options)| (Context::Output(context), options)),
     }
 }
 
 pub fn input<P: AsRef<Path>>(path: &P) -> Result<context::Input, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-
-        match avformat_open_input(&mut ps, path.as_ptr(), ptr::null_mut(), ptr::null_mut()) {
-            0 => match avformat_find_stream_info(ps, ptr::null_mut()) {
-                r if r >= 0 => Ok(context::Input::wrap(ps)),
-                e => {
-                    avformat_close_input(&mut ps);
-                    Err(Error::from(e))
-                }
-            },
-
-            e => Err(Error::from(e)),
-        }
-    }
+    InputBuilder::new(path)
+        .open()
+        .map(|(context, _, _)| context)
 }
 
-pub fn input_with_dictionary<P: AsRef<Path>>(
+pub fn input_with_dictionary<'a, P: AsRef<Path>>(
     path: &P,
-    options: Dictionary,
-) -> Result<context::Input, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-        let mut opts = options.disown();
-        let res = avformat_open_input(&mut ps, path.as_ptr(), ptr::null_mut(), &mut opts);
-
-        Dictionary::own(opts);
-
-        match res {
-            0 => match avformat_find_stream_info(ps, ptr::null_mut()) {
-                r if r >= 0 => Ok(context::Input::wrap(ps)),
-                e => {
-                    avformat_close_input(&mut ps);
-                    Err(Error::from(e))
-                }
-            },
-
-            e => Err(Error::from(e)),
-        }
-    }
+    options: Dictionary<'a>,
+) -> Result<(context::Input, Dictionary<'a>), Error> {
+    InputBuilder::new(path)
+        .options(options)
+        .open()
+        .map(|(context, options, _)| (context, options))
 }
 
 pub fn input_with_interrupt<P: AsRef<Path>, F>(
@@ -194,129 +173,80 @@ pub fn input_with_interrupt<P: AsRef<Path>, F>(
     closure: F,
 ) -> Result<context::Input, Error>
 where
-    F: FnMut() -> bool,
+    F: FnMut() -> bool + Send + 'static,
 {
-    unsafe {
-        let mut ps = avformat_alloc_context();
-        let path = from_path(path);
-        (*ps).interrupt_callback = interrupt::new(Box::new(closure)).interrupt;
-
-        match avformat_open_input(&mut ps, path.as_ptr(), ptr::null_mut(), ptr::null_mut()) {
-            0 => match avformat_find_stream_info(ps, ptr::null_mut()) {
-                r if r >= 0 => Ok(context::Input::wrap(ps)),
-                e => {
-                    avformat_close_input(&mut ps);
-                    Err(Error::from(e))
-                }
-            },
+    InputBuilder::new(path)
+        .interrupt(closure)
+        .open()
+        .map(|(context, _, _)| context)
+}
 
-            e => Err(Error::from(e)),
-        }
-    }
+pub fn input_from(io: Io) -> Result<context::Input, Error> {
+    InputBuilder::with_io(io)
+        .open()
+        .map(|(context, _, _)| context)
 }
 
 pub fn output<P: AsRef<Path>>(path: &P) -> Result<context::Output, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-
-        match avformat_alloc_output_context2(&mut ps, ptr::null_mut(), ptr::null(), path.as_ptr()) {
-            0 => match avio_open(&mut (*ps).pb, path.as_ptr(), AVIO_FLAG_WRITE) {
-                0 => Ok(context::Output::wrap(ps)),
-                e => Err(Error::from(e)),
-            },
-
-            e => Err(Error::from(e)),
-        }
-    }
+    OutputBuilder::new(path)
+        .open()
+        .map(|(context, _, _)| context)
 }
 
-pub fn output_with<P: AsRef<Path>>(
+pub fn output_with<'a, P: AsRef<Path>>(
     path: &P,
-    options: Dictionary,
-) -> Result<context::Output, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-        let mut opts = options.disown();
-
-        match avformat_alloc_output_context2(&mut ps, ptr::null_mut(), ptr::null(), path.as_ptr()) {
-            0 => {
-                let res = avio_open2(
-                    &mut (*ps).pb,
-                    path.as_ptr(),
-                    AVIO_FLAG_WRITE,
-                    ptr::null(),
-                    &mut opts,
-                );
-
-                Dictionary::own(opts);
-
-                match res {
-                    0 => Ok(context::Output::wrap(ps)),
-                    e => Err(Error::from(e)),
-                }
-            }
-
-            e => Err(Error::from(e)),
-        }
-    }
+    options: Dictionary<'a>,
+) -> Result<(context::Output, Dictionary<'a>), Error> {
+    OutputBuilder::new(path)
+        .io_options(options)
+        .open()
+        .map(|(context, _, options)| (context, options))
 }
 
 pub fn output_as<P: AsRef<Path>>(path: &P, format: &str) -> Result<context::Output, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-        let format = CString::new(format).unwrap();
-
-        match avformat_alloc_output_context2(
-            &mut ps,
-            ptr::null_mut(),
-            format.as_ptr(),
-            path.as_ptr(),
-        ) {
-            0 => match avio_open(&mut (*ps).pb, path.as_ptr(), AVIO_FLAG_WRITE) {
-                0 => Ok(context::Output::wrap(ps)),
-                e => Err(Error::from(e)),
-            },
-
-            e => Err(Error::from(e)),
-        }
-    }
+    OutputBuilder::new(path)
+        .format(format)
+        .open()
+        .map(|(context, _, _)| context)
 }
 
-pub fn output_as_with<P: AsRef<Path>>(
+pub fn output_as_with<'a, P: AsRef<Path>>(
     path: &P,
     format: &str,
-    options: Dictionary,
-) -> Result<context::Output, Error> {
-    unsafe {
-        let mut ps = ptr::null_mut();
-        let path = from_path(path);
-        let format = CString::new(format).unwrap();
-        let mut opts = options.disown();
+    options: Dictionary<'a>,
+) -> Result<(context::Output, Dictionary<'a>), Error> {
+    OutputBuilder::new(path)
+        .format(format)
+        .io_options(options)
+        .open()
+        .map(|(context, _, options)| (context, options))
+}
 
-        match avformat_alloc_output_context2(
-            &mut ps,
-            ptr::null_mut(),
-            format.as_ptr(),
-            path.as_ptr(),
+pub fn output_to(io: Io, format: &str) -> Result<context::Output, Error> {
+    OutputBuilder::with_io(io)
+        .format(format)
+        .open()
+        .map(|(context, _, _)| context)
+}
+
+pub fn sdp(outputs: &[&context::Output]) -> Result<String, Error> {
+    // same size ffmpeg uses, av_sdp_create truncates anything longer
+    let mut buffer = vec![0u8; 16 * 1024];
+    let mut contexts = outputs
+        .iter()
+        .map(|o| unsafe { o.as_ptr() as *mut AVFormatContext })
+        .collect::<Vec<_>>();
+
+    unsafe {
+        match av_sdp_create(
+            contexts.as_mut_ptr(),
+            contexts.len() as c_int,
+            buffer.as_mut_ptr() as *mut _,
+            buffer.len() as c_int,
         ) {
             0 => {
-                let res = avio_open2(
-                    &mut (*ps).pb,
-                    path.as_ptr(),
-                    AVIO_FLAG_WRITE,
-                    ptr::null(),
-                    &mut opts,
-                );
-
-                Dictionary::own(opts);
-
-                match res {
-                    0 => Ok(context::Output::wrap(ps)),
-                    e => Err(Error::from(e)),
-                }
+                let sdp = CStr::from_ptr(buffer.as_ptr() as *const _);
+                Ok(String::from_utf8_lossy(sdp.to_bytes()).into_owned())
             }
 
             e => Err(Error::from(e)),
@@ -1,4 +1,10 @@
+use std::ffi::CString;
+use std::io::Cursor;
+use std::time::Duration;
+
 use ffi::*;
+use format::{self, context, InputBuilder, Io, OutputBuilder};
+use {rescale, Dictionary, Error};
 
 pub fn init() {
     unsafe {
@@ -11,3 +17,192 @@ pub fn deinit() {
         avformat_network_deinit();
     }
 }
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Protocol {
+    Udp,
+    Tcp,
+    Rtp,
+}
+
+impl Protocol {
+    pub fn scheme(&self) -> &'static str {
+        match *self {
+            Protocol::Udp => "udp",
+            Protocol::Tcp => "tcp",
+            Protocol::Rtp => "rtp",
+        }
+    }
+
+    // udp and tcp carry mpegts, rtp has its own muxer with one stream per
+    // output
+    pub fn format(&self) -> &'static str {
+        match *self {
+            Protocol::Udp | Protocol::Tcp => "mpegts",
+            Protocol::Rtp => "rtp",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    protocol: Protocol,
+    host: String,
+    port: u16,
+
+    packet_size: Option<usize>,
+    bitrate: Option<u64>,
+    ttl: Option<u8>,
+    listen: bool,
+    timeout: Option<Duration>,
+}
+
+impl Endpoint {
+    pub fn new(protocol: Protocol, host: &str, port: u16) -> Self {
+        Endpoint {
+            protocol: protocol,
+            host: host.to_owned(),
+            port: port,
+
+            packet_size: None,
+            bitrate: None,
+            ttl: None,
+            listen: false,
+            timeout: None,
+        }
+    }
+
+    pub fn udp(host: &str, port: u16) -> Self {
+        Endpoint::new(Protocol::Udp, host, port)
+    }
+
+    pub fn tcp(host: &str, port: u16) -> Self {
+        Endpoint::new(Protocol::Tcp, host, port)
+    }
+
+    pub fn rtp(host: &str, port: u16) -> Self {
+        Endpoint::new(Protocol::Rtp, host, port)
+    }
+
+    pub fn packet_size(mut self, value: usize) -> Self {
+        self.packet_size = Some(value);
+        self
+    }
+
+    // only udp paces packets, the other protocols reject it
+    pub fn bitrate(mut self, value: u64) -> Self {
+        self.bitrate = Some(value);
+        self
+    }
+
+    pub fn ttl(mut self, value: u8) -> Self {
+        self.ttl = Some(value);
+        self
+    }
+
+    pub fn listen(mut self, value: bool) -> Self {
+        self.listen = value;
+        self
+    }
+
+    pub fn timeout(mut self, value: Duration) -> Self {
+        self.timeout = Some(value);
+        self
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn url(&self) -> String {
+        if self.host.contains(':') {
+            format!("{}://[{}]:{}", self.protocol.scheme(), self.host, self.port)
+        } else {
+            format!("{}://{}:{}", self.protocol.scheme(), self.host, self.port)
+        }
+    }
+
+    // options the protocol has no use for are rejected rather than dropped
+    pub fn options<'a>(&self) -> Result<Dictionary<'a>, Error> {
+        let mut options = Dictionary::new();
+
+        if let Some(size) = self.packet_size {
+            options.set("pkt_size", &size.to_string());
+        }
+
+        match (self.protocol, self.bitrate) {
+            (_, None) => (),
+            (Protocol::Udp, Some(bitrate)) => options.set("bitrate", &bitrate.to_string()),
+            _ => return Err(Error::InvalidArgument),
+        }
+
+        match (self.protocol, self.ttl) {
+            (_, None) => (),
+            (Protocol::Tcp, Some(..)) => return Err(Error::InvalidArgument),
+            (_, Some(ttl)) => options.set("ttl", &ttl.to_string()),
+        }
+
+        match (self.protocol, self.listen) {
+            (_, false) => (),
+            (Protocol::Tcp, true) => options.set("listen", "1"),
+            _ => return Err(Error::InvalidArgument),
+        }
+
+        if let Some(timeout) = self.timeout {
+            options.set("timeout", &rescale::from_duration(timeout).to_string());
+        }
+
+        Ok(options)
+    }
+
+    pub fn output_builder<'a>(&self) -> Result<OutputBuilder<'a>, Error> {
+        Ok(OutputBuilder::new(&self.url())
+            .format(self.protocol.format())
+            .io_options(self.options()?))
+    }
+
+    pub fn output<'a>(&self) -> Result<(context::Output, Dictionary<'a>), Error> {
+        self.output_builder()?
+            .open()
+            .map(|(context, _, options)| (context, options))
+    }
+
+    // rtp needs an sdp to be received, use `sdp_input` for that instead
+    pub fn input_builder<'a>(&self) -> Result<InputBuilder<'a>, Error> {
+        match self.protocol {
+            Protocol::Rtp => Err(Error::InvalidArgument),
+            _ => Ok(InputBuilder::new(&self.url()).options(self.options()?)),
+        }
+    }
+
+    pub fn input<'a>(&self) -> Result<(context::Input, Dictionary<'a>), Error> {
+        self.input_builder()?
+            .open()
+            .map(|(context, options, _)| (context, options))
+    }
+}
+
+// rtp streams are received through the session description the sender got
+// from `format::sdp`
+pub fn sdp_input<'a>(sdp: &str) -> Result<InputBuilder<'a>, Error> {
+    let name = CString::new("sdp").unwrap();
+
+    unsafe {
+        let ptr = av_find_input_format(name.as_ptr());
+
+        if ptr.is_null() {
+            return Err(Error::DemuxerNotFound);
+        }
+
+        // the description is not read from a file, only let it open the
+        // streams it lists
+        let mut options = Dictionary::new();
+        options.set("protocol_whitelist", "udp,rtp");
+
+        Ok(
+            InputBuilder::with_io(Io::reader(Cursor::new(sdp.as_bytes().to_vec())))
+                .format(format::Input::wrap(ptr as *mut _))
+                .options(options),
+        )
+    }
+}
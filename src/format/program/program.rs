@@ -0,0 +1,79 @@
+use std::slice;
+
+use ffi::*;
+use {DictionaryRef, Discard};
+
+use format::context::common::Context;
+
+// WARNING: index refers to the offset in the programs array (starting from 0)
+// it is not the program id
+pub struct Program<'a> {
+    context: &'a Context,
+    index: usize,
+}
+
+impl<'a> Program<'a> {
+    pub unsafe fn wrap(context: &Context, index: usize) -> Program {
+        Program {
+            context: context,
+            index: index,
+        }
+    }
+
+    pub unsafe fn as_ptr(&self) -> *const AVProgram {
+        *(*self.context.as_ptr())
+            .programs
+            .offset(self.index as isize)
+    }
+}
+
+impl<'a> Program<'a> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn id(&self) -> i32 {
+        unsafe { (*self.as_ptr()).id }
+    }
+
+    pub fn number(&self) -> i32 {
+        unsafe { (*self.as_ptr()).program_num }
+    }
+
+    pub fn pmt_pid(&self) -> i32 {
+        unsafe { (*self.as_ptr()).pmt_pid }
+    }
+
+    pub fn pcr_pid(&self) -> i32 {
+        unsafe { (*self.as_ptr()).pcr_pid }
+    }
+
+    pub fn discard(&self) -> Discard {
+        unsafe { Discard::from((*self.as_ptr()).discard) }
+    }
+
+    pub fn stream_indices(&self) -> Vec<usize> {
+        unsafe {
+            let ptr = self.as_ptr();
+
+            if (*ptr).stream_index.is_null() {
+                return Vec::new();
+            }
+
+            slice::from_raw_parts((*ptr).stream_index, (*ptr).nb_stream_indexes as usize)
+                .iter()
+                .map(|&index| index as usize)
+                .collect()
+        }
+    }
+
+    pub fn metadata(&self) -> DictionaryRef {
+        unsafe { DictionaryRef::wrap((*self.as_ptr()).metadata) }
+    }
+}
+
+impl<'a> PartialEq for Program<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { self.as_ptr() == other.as_ptr() }
+    }
+}
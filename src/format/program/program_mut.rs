@@ -0,0 +1,82 @@
+use std::mem;
+use std::ops::Deref;
+
+use super::Program;
+use ffi::*;
+use format::context::common::Context;
+use libc::c_uint;
+use {Dictionary, Error};
+
+// WARNING: index refers to the offset in the programs array (starting from 0)
+// it is not the program id
+pub struct ProgramMut<'a> {
+    context: &'a mut Context,
+    index: usize,
+
+    immutable: Program<'a>,
+}
+
+impl<'a> ProgramMut<'a> {
+    pub unsafe fn wrap(context: &mut Context, index: usize) -> ProgramMut {
+        ProgramMut {
+            context: mem::transmute_copy(&context),
+            index: index,
+
+            immutable: Program::wrap(mem::transmute_copy(&context), index),
+        }
+    }
+
+    pub unsafe fn as_mut_ptr(&mut self) -> *mut AVProgram {
+        *(*self.context.as_mut_ptr())
+            .programs
+            .offset(self.index as isize)
+    }
+}
+
+impl<'a> ProgramMut<'a> {
+    pub fn set_number(&mut self, value: i32) {
+        unsafe {
+            (*self.as_mut_ptr()).program_num = value;
+        }
+    }
+
+    pub fn set_pmt_pid(&mut self, value: i32) {
+        unsafe {
+            (*self.as_mut_ptr()).pmt_pid = value;
+        }
+    }
+
+    pub fn set_pcr_pid(&mut self, value: i32) {
+        unsafe {
+            (*self.as_mut_ptr()).pcr_pid = value;
+        }
+    }
+
+    pub fn set_metadata(&mut self, metadata: Dictionary) {
+        unsafe {
+            let metadata = metadata.disown();
+            (*self.as_mut_ptr()).metadata = metadata;
+        }
+    }
+
+    pub fn add_stream(&mut self, index: usize) -> Result<(), Error> {
+        unsafe {
+            if index >= (*self.context.as_ptr()).nb_streams as usize {
+                return Err(Error::StreamNotFound);
+            }
+
+            // does nothing if the stream is already part of the program
+            av_program_add_stream_index(self.context.as_mut_ptr(), self.id(), index as c_uint);
+
+            Ok(())
+        }
+    }
+}
+
+impl<'a> Deref for ProgramMut<'a> {
+    type Target = Program<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.immutable
+    }
+}
@@ -0,0 +1,82 @@
+use codec::packet::Mut;
+use ffi::*;
+use format::context::{Input, Output};
+use {Error, Packet, Rational, Stream};
+
+pub fn remux(input: &mut Input, output: &mut Output, streams: &[usize]) -> Result<(), Error> {
+    let mut mapping = vec![None; input.streams().count()];
+
+    for &index in streams {
+        let stream = input.stream(index).ok_or(Error::StreamNotFound)?;
+
+        if mapping[index].is_none() {
+            mapping[index] = Some(unsafe { add(output, &stream)? });
+        }
+    }
+
+    // let the muxer shift timestamps so the first DTS starts at zero instead
+    // of rejecting streams with B-frames or a negative start time
+    unsafe {
+        (*output.as_mut_ptr()).avoid_negative_ts = AVFMT_AVOID_NEG_TS_MAKE_ZERO;
+    }
+
+    output.write_header()?;
+
+    // the muxer may change the stream time bases while writing the header
+    let time_bases = mapping
+        .iter()
+        .enumerate()
+        .map(|(index, target)| {
+            target.map(|target| {
+                (
+                    input.stream(index).unwrap().time_base(),
+                    output.stream(target).unwrap().time_base(),
+                )
+            })
+        })
+        .collect::<Vec<Option<(Rational, Rational)>>>();
+
+    let mut packet = Packet::empty();
+
+    loop {
+        match packet.read(input) {
+            Ok(..) => (),
+            Err(Error::Eof) => break,
+            Err(e) => return Err(e),
+        }
+
+        let index = packet.stream();
+        let (target, (source, destination)) = match (mapping.get(index), time_bases.get(index)) {
+            (Some(&Some(target)), Some(&Some(time_bases))) => (target, time_bases),
+            _ => continue,
+        };
+
+        packet.rescale_ts(source, destination);
+        packet.set_stream(target);
+
+        unsafe {
+            (*packet.as_mut_ptr()).pos = -1;
+        }
+
+        packet.write_interleaved(output)?;
+    }
+
+    output.write_trailer()
+}
+
+pub fn remux_all(input: &mut Input, output: &mut Output) -> Result<(), Error> {
+    let streams = input.streams().map(|s| s.index()).collect::<Vec<_>>();
+
+    remux(input, output, &streams)
+}
+
+unsafe fn add(output: &mut Output, stream: &Stream) -> Result<usize, Error> {
+    let mut copy = output.add_copy_stream(stream.parameters())?;
+    let ptr = copy.as_mut_ptr();
+
+    (*ptr).time_base = (*stream.as_ptr()).time_base;
+    (*ptr).disposition = (*stream.as_ptr()).disposition;
+    av_dict_copy(&mut (*ptr).metadata, (*stream.as_ptr()).metadata, 0);
+
+    Ok(copy.index())
+}
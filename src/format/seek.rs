@@ -0,0 +1,18 @@
+use ffi::*;
+use libc::c_int;
+
+bitflags! {
+    pub struct Flags: c_int {
+        const BACKWARD = AVSEEK_FLAG_BACKWARD;
+        const BYTE     = AVSEEK_FLAG_BYTE;
+        const ANY      = AVSEEK_FLAG_ANY;
+        const FRAME    = AVSEEK_FLAG_FRAME;
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Landing {
+    Keyframe,
+    NonKeyframe,
+    Unknown,
+}
@@ -0,0 +1,242 @@
+use std::cmp;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use ffi::*;
+use format::{context, Io, OutputBuilder};
+use {codec, media, rescale, Dictionary, Error, Packet, Rational, Rescale};
+
+pub enum Target {
+    File(PathBuf),
+    Io(Io),
+}
+
+#[derive(Clone, Debug)]
+pub struct Segment {
+    pub index: usize,
+    pub path: Option<PathBuf>,
+    pub start: i64,
+    pub duration: i64,
+    pub time_base: Rational,
+}
+
+struct Template {
+    parameters: codec::Parameters,
+    time_base: Rational,
+}
+
+struct Current {
+    output: context::Output,
+    index: usize,
+    path: Option<PathBuf>,
+    start: i64,
+    end: i64,
+    bytes: usize,
+}
+
+// Packets are expected in the time base given to `add_stream`, segments are
+// cut on keyframes of the first video stream (or the first stream if there is
+// no video) and the reported start and duration are in that stream time base.
+pub struct Segmenter<'a> {
+    format: String,
+    options: Dictionary<'a>,
+    duration: Option<i64>,
+    size: Option<usize>,
+
+    next: Box<dyn FnMut(usize) -> Result<Target, Error> + 'a>,
+    finished: Option<Box<dyn FnMut(Segment) + 'a>>,
+
+    streams: Vec<Template>,
+    current: Option<Current>,
+    count: usize,
+}
+
+impl<'a> Segmenter<'a> {
+    pub fn new<F>(format: &str, next: F) -> Self
+    where
+        F: FnMut(usize) -> Result<Target, Error> + 'a,
+    {
+        Segmenter {
+            format: format.to_owned(),
+            options: Dictionary::new(),
+            duration: None,
+            size: None,
+
+            next: Box::new(next),
+            finished: None,
+
+            streams: Vec::new(),
+            current: None,
+            count: 0,
+        }
+    }
+
+    pub fn duration(mut self, value: Duration) -> Self {
+        self.duration = Some(rescale::from_duration(value));
+        self
+    }
+
+    pub fn size(mut self, value: usize) -> Self {
+        self.size = Some(value);
+        self
+    }
+
+    pub fn options(mut self, options: Dictionary<'a>) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn on_segment<F>(mut self, closure: F) -> Self
+    where
+        F: FnMut(Segment) + 'a,
+    {
+        self.finished = Some(Box::new(closure));
+        self
+    }
+
+    pub fn add_stream<P, R>(&mut self, parameters: P, time_base: R) -> usize
+    where
+        P: Into<codec::Parameters>,
+        R: Into<Rational>,
+    {
+        self.streams.push(Template {
+            parameters: parameters.into(),
+            time_base: time_base.into(),
+        });
+
+        self.streams.len() - 1
+    }
+
+    // the packet is rebased and rescaled for the segment, so it is taken by
+    // value; returns false when it was dropped because no segment is open
+    // yet, which is the case until the first keyframe of the reference stream
+    pub fn write(&mut self, mut packet: Packet) -> Result<bool, Error> {
+        let index = packet.stream();
+        let time_base = self
+            .streams
+            .get(index)
+            .ok_or(Error::StreamNotFound)?
+            .time_base;
+        let reference = self.reference();
+        let is_reference = index == reference;
+        let pts = packet.pts().or_else(|| packet.dts());
+
+        if let (true, Some(pts)) = (is_reference && packet.is_key(), pts) {
+            let rotate = match self.current {
+                Some(ref current) => self.is_full(current, pts),
+                None => true,
+            };
+
+            if rotate {
+                self.close(Some(pts))?;
+                self.open(pts)?;
+            }
+        }
+
+        let reference = self.streams[reference].time_base;
+        let current = match self.current {
+            Some(ref mut current) => current,
+
+            // nothing can be written before the first keyframe
+            None => return Ok(false),
+        };
+
+        if let (true, Some(pts)) = (is_reference, pts) {
+            current.end = cmp::max(current.end, pts + packet.duration());
+        }
+
+        let offset = current.start.rescale(reference, time_base);
+        let destination = current.output.stream(index).unwrap().time_base();
+
+        let pts = packet.pts().map(|v| v - offset);
+        let dts = packet.dts().map(|v| v - offset);
+        packet.set_pts(pts);
+        packet.set_dts(dts);
+        packet.rescale_ts(time_base, destination);
+
+        current.bytes += packet.size();
+        packet.write_interleaved(&mut current.output)?;
+
+        Ok(true)
+    }
+
+    pub fn finish(&mut self) -> Result<(), Error> {
+        self.close(None)
+    }
+
+    fn reference(&self) -> usize {
+        self.streams
+            .iter()
+            .position(|s| s.parameters.medium() == media::Type::Video)
+            .unwrap_or(0)
+    }
+
+    fn is_full(&self, current: &Current, pts: i64) -> bool {
+        let time_base = self.streams[self.reference()].time_base;
+        let elapsed = (pts - current.start).rescale(time_base, rescale::TIME_BASE);
+
+        self.duration.map_or(false, |d| elapsed >= d)
+            || self.size.map_or(false, |s| current.bytes >= s)
+    }
+
+    fn open(&mut self, start: i64) -> Result<(), Error> {
+        let (builder, path) = match (self.next)(self.count)? {
+            Target::File(path) => (OutputBuilder::new(&path), Some(path)),
+            Target::Io(io) => (OutputBuilder::with_io(io), None),
+        };
+
+        let (mut output, _, _) = builder
+            .format(&self.format)
+            .options(self.options.clone())
+            .open()?;
+
+        for template in &self.streams {
+            let mut stream = output.add_copy_stream(template.parameters.clone())?;
+            stream.set_time_base(template.time_base);
+        }
+
+        unsafe {
+            (*output.as_mut_ptr()).avoid_negative_ts = AVFMT_AVOID_NEG_TS_MAKE_ZERO;
+        }
+
+        output.write_header()?;
+
+        self.current = Some(Current {
+            output: output,
+            index: self.count,
+            path: path,
+            start: start,
+            end: start,
+            bytes: 0,
+        });
+        self.count += 1;
+
+        Ok(())
+    }
+
+    fn close(&mut self, end: Option<i64>) -> Result<(), Error> {
+        let mut current = match self.current.take() {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+
+        current.output.write_trailer()?;
+
+        let segment = Segment {
+            index: current.index,
+            path: current.path.take(),
+            start: current.start,
+            duration: end.unwrap_or(current.end) - current.start,
+            time_base: self.streams[self.reference()].time_base,
+        };
+
+        // close the file before handing it out
+        drop(current);
+
+        if let Some(ref mut finished) = self.finished {
+            finished(segment);
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
-use super::Disposition;
+use std::slice;
+
+use super::{disposition, Disposition};
 use codec::{self, packet};
 use ffi::*;
 use format::context::common::Context;
@@ -62,6 +64,18 @@ impl<'a> Stream<'a> {
         unsafe { Disposition::from_bits_truncate((*self.as_ptr()).disposition) }
     }
 
+    pub fn attached_pic(&self) -> Option<&[u8]> {
+        unsafe {
+            let packet = &(*self.as_ptr()).attached_pic;
+
+            if !self.disposition().contains(disposition::ATTACHED_PIC) || packet.data.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(packet.data, packet.size as usize))
+            }
+        }
+    }
+
     pub fn discard(&self) -> Discard {
         unsafe { Discard::from((*self.as_ptr()).discard) }
     }
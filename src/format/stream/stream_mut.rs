@@ -4,7 +4,7 @@ use std::ops::Deref;
 use super::Stream;
 use ffi::*;
 use format::context::common::Context;
-use {codec, Dictionary, Rational};
+use {codec, Dictionary, Discard, Rational};
 
 pub struct StreamMut<'a> {
     context: &'a mut Context,
@@ -49,6 +49,12 @@ impl<'a> StreamMut<'a> {
         }
     }
 
+    pub fn set_discard(&mut self, value: Discard) {
+        unsafe {
+            (*self.as_mut_ptr()).discard = value.into();
+        }
+    }
+
     pub fn set_parameters<P: Into<codec::Parameters>>(&mut self, parameters: P) {
         let parameters = parameters.into();
 
@@ -12,6 +12,7 @@ pub use sys as ffi;
 
 #[macro_use]
 pub mod util;
+pub use util::cancel;
 pub use util::channel_layout::{self, ChannelLayout};
 pub use util::chroma;
 pub use util::color;
@@ -35,6 +36,8 @@ pub use format::chapter::{Chapter, ChapterMut};
 #[cfg(feature = "format")]
 pub use format::format::Format;
 #[cfg(feature = "format")]
+pub use format::program::{Program, ProgramMut};
+#[cfg(feature = "format")]
 pub use format::stream::{Stream, StreamMut};
 
 #[cfg(feature = "codec")]
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use Error;
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Reason {
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+struct Run {
+    started: Option<Instant>,
+    expired: bool,
+}
+
+#[derive(Debug)]
+struct State {
+    timeout: Duration,
+    runs: Mutex<HashMap<ThreadId, Run>>,
+}
+
+// A deadline only runs between `start` and `stop`, so the same value can be
+// reused to bound every open, read or write on a context. Contexts and
+// builders given a `Cancellation` arm it around each of those calls, an
+// expired deadline stays reported until the next one starts.
+//
+// libavformat polls the interrupt on the thread doing the operation, so the
+// clock is kept per thread and contexts used from different threads can share
+// the same deadline without cutting each other short.
+#[derive(Clone, Debug)]
+pub struct Deadline {
+    state: Arc<State>,
+}
+
+impl Deadline {
+    pub fn new(timeout: Duration) -> Self {
+        Deadline {
+            state: Arc::new(State {
+                timeout: timeout,
+                runs: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.state.timeout
+    }
+
+    pub fn start(&self) {
+        let run = Run {
+            started: Some(Instant::now()),
+            expired: false,
+        };
+
+        self.state
+            .runs
+            .lock()
+            .unwrap()
+            .insert(thread::current().id(), run);
+    }
+
+    pub fn stop(&self) {
+        let mut runs = self.state.runs.lock().unwrap();
+        let id = thread::current().id();
+
+        // expired runs are kept so `is_expired` can still report them
+        let expired = match runs.get_mut(&id) {
+            Some(run) => {
+                run.started = None;
+                run.expired
+            }
+
+            None => return,
+        };
+
+        if !expired {
+            runs.remove(&id);
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.current().expired
+    }
+
+    pub fn run<T, F>(&self, operation: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> Result<T, Error>,
+    {
+        // nested operations are bounded by the outermost one
+        if self.current().started.is_some() {
+            return operation();
+        }
+
+        self.start();
+        let result = operation();
+        self.stop();
+
+        result
+    }
+
+    fn current(&self) -> Run {
+        self.state
+            .runs
+            .lock()
+            .unwrap()
+            .get(&thread::current().id())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn check(&self) -> bool {
+        let mut runs = self.state.runs.lock().unwrap();
+
+        match runs.get_mut(&thread::current().id()) {
+            Some(run) => match run.started {
+                Some(started) if started.elapsed() >= self.state.timeout => {
+                    run.expired = true;
+                    true
+                }
+
+                _ => false,
+            },
+
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Cancellation {
+    token: Option<CancellationToken>,
+    deadline: Option<Deadline>,
+}
+
+impl Cancellation {
+    pub fn new() -> Self {
+        Cancellation {
+            token: None,
+            deadline: None,
+        }
+    }
+
+    pub fn token(mut self, token: &CancellationToken) -> Self {
+        self.token = Some(token.clone());
+        self
+    }
+
+    pub fn deadline(mut self, deadline: &Deadline) -> Self {
+        self.deadline = Some(deadline.clone());
+        self
+    }
+
+    pub fn run<T, F>(&self, operation: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> Result<T, Error>,
+    {
+        match self.deadline {
+            Some(ref deadline) => deadline.run(operation),
+            None => operation(),
+        }
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.token.as_ref().map_or(false, |t| t.is_cancelled())
+            || self.deadline.as_ref().map_or(false, |d| d.check())
+    }
+
+    pub fn reason(&self) -> Option<Reason> {
+        if self.token.as_ref().map_or(false, |t| t.is_cancelled()) {
+            Some(Reason::Cancelled)
+        } else if self.deadline.as_ref().map_or(false, |d| d.is_expired()) {
+            Some(Reason::TimedOut)
+        } else {
+            None
+        }
+    }
+
+    pub fn reason_for(&self, error: &Error) -> Option<Reason> {
+        match *error {
+            Error::Exit => self.reason(),
+            _ => None,
+        }
+    }
+}
@@ -5,9 +5,15 @@ use std::io;
 use std::str::from_utf8_unchecked;
 
 use ffi::*;
-use libc::c_int;
+use libc::{
+    c_char, c_int, EACCES, EAGAIN, ECONNREFUSED, ECONNRESET, EINTR, EINVAL, EIO, ENOENT, ENOMEM,
+    EPIPE, ETIMEDOUT,
+};
 
-#[derive(Copy, Clone)]
+// errno values never go past this, FFERRTAG codes are well above it
+const MAX_ERRNO: c_int = 4095;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub enum Error {
     Bug,
     Bug2,
@@ -39,6 +45,20 @@ pub enum Error {
     HttpNotFound,
     HttpOther4xx,
     HttpServerError,
+
+    Again,
+    NoMemory,
+    InvalidArgument,
+    Io,
+    TimedOut,
+    NotFound,
+    PermissionDenied,
+    Interrupted,
+    BrokenPipe,
+    ConnectionRefused,
+    ConnectionReset,
+
+    Other { errno: c_int },
 }
 
 impl From<c_int> for Error {
@@ -72,7 +92,22 @@ impl From<c_int> for Error {
             AVERROR_HTTP_OTHER_4XX => Error::HttpOther4xx,
             AVERROR_HTTP_SERVER_ERROR => Error::HttpServerError,
 
-            _ => Error::Unknown,
+            e => match AVUNERROR(e) {
+                EAGAIN => Error::Again,
+                ENOMEM => Error::NoMemory,
+                EINVAL => Error::InvalidArgument,
+                EIO => Error::Io,
+                ETIMEDOUT => Error::TimedOut,
+                ENOENT => Error::NotFound,
+                EACCES => Error::PermissionDenied,
+                EINTR => Error::Interrupted,
+                EPIPE => Error::BrokenPipe,
+                ECONNREFUSED => Error::ConnectionRefused,
+                ECONNRESET => Error::ConnectionReset,
+
+                errno if errno > 0 && errno <= MAX_ERRNO => Error::Other { errno: errno },
+                _ => Error::Unknown,
+            },
         }
     }
 }
@@ -107,19 +142,94 @@ impl Into<c_int> for Error {
             Error::HttpNotFound => AVERROR_HTTP_NOT_FOUND,
             Error::HttpOther4xx => AVERROR_HTTP_OTHER_4XX,
             Error::HttpServerError => AVERROR_HTTP_SERVER_ERROR,
+
+            Error::Again => AVERROR(EAGAIN),
+            Error::NoMemory => AVERROR(ENOMEM),
+            Error::InvalidArgument => AVERROR(EINVAL),
+            Error::Io => AVERROR(EIO),
+            Error::TimedOut => AVERROR(ETIMEDOUT),
+            Error::NotFound => AVERROR(ENOENT),
+            Error::PermissionDenied => AVERROR(EACCES),
+            Error::Interrupted => AVERROR(EINTR),
+            Error::BrokenPipe => AVERROR(EPIPE),
+            Error::ConnectionRefused => AVERROR(ECONNREFUSED),
+            Error::ConnectionReset => AVERROR(ECONNRESET),
+
+            Error::Other { errno } => AVERROR(errno),
+        }
+    }
+}
+
+impl Error {
+    pub fn errno(&self) -> Option<c_int> {
+        match *self {
+            Error::Again => Some(EAGAIN),
+            Error::NoMemory => Some(ENOMEM),
+            Error::InvalidArgument => Some(EINVAL),
+            Error::Io => Some(EIO),
+            Error::TimedOut => Some(ETIMEDOUT),
+            Error::NotFound => Some(ENOENT),
+            Error::PermissionDenied => Some(EACCES),
+            Error::Interrupted => Some(EINTR),
+            Error::BrokenPipe => Some(EPIPE),
+            Error::ConnectionRefused => Some(ECONNREFUSED),
+            Error::ConnectionReset => Some(ECONNRESET),
+            Error::Other { errno } => Some(errno),
+
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for io::ErrorKind {
+    fn from(value: Error) -> io::ErrorKind {
+        match value {
+            Error::Eof => io::ErrorKind::UnexpectedEof,
+            // Interrupted is retried by std, the operation was aborted on purpose
+            Error::Exit => io::ErrorKind::Other,
+            Error::InvalidData => io::ErrorKind::InvalidData,
+
+            Error::BsfNotFound
+            | Error::DecoderNotFound
+            | Error::DemuxerNotFound
+            | Error::EncoderNotFound
+            | Error::OptionNotFound
+            | Error::MuxerNotFound
+            | Error::FilterNotFound
+            | Error::ProtocolNotFound
+            | Error::StreamNotFound
+            | Error::HttpNotFound => io::ErrorKind::NotFound,
+
+            Error::HttpUnauthorized | Error::HttpForbidden => io::ErrorKind::PermissionDenied,
+
+            e => match e.errno() {
+                Some(errno) => io::Error::from_raw_os_error(errno).kind(),
+                None => io::ErrorKind::Other,
+            },
         }
     }
 }
 
 impl From<Error> for io::Error {
     fn from(value: Error) -> io::Error {
-        io::Error::new(io::ErrorKind::Other, value)
+        io::Error::new(io::ErrorKind::from(value), value)
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        f.write_str(error::Error::description(self))
+        let mut buffer = [0 as c_char; AV_ERROR_MAX_STRING_SIZE as usize];
+
+        unsafe {
+            av_strerror(
+                (*self).into(),
+                buffer.as_mut_ptr(),
+                AV_ERROR_MAX_STRING_SIZE,
+            );
+            f.write_str(&String::from_utf8_lossy(
+                CStr::from_ptr(buffer.as_ptr()).to_bytes(),
+            ))
+        }
     }
 }
 
@@ -162,12 +272,25 @@ fn index(error: &Error) -> usize {
         Error::HttpNotFound => 24,
         Error::HttpOther4xx => 25,
         Error::HttpServerError => 26,
+
+        Error::Again => 27,
+        Error::NoMemory => 28,
+        Error::InvalidArgument => 29,
+        Error::Io => 30,
+        Error::TimedOut => 31,
+        Error::NotFound => 32,
+        Error::PermissionDenied => 33,
+        Error::Interrupted => 34,
+        Error::BrokenPipe => 35,
+        Error::ConnectionRefused => 36,
+        Error::ConnectionReset => 37,
+        Error::Other { .. } => 38,
     }
 }
 
 // XXX: the length has to be synced with the number of errors
-static mut STRINGS: [[i8; AV_ERROR_MAX_STRING_SIZE as usize]; 27] =
-    [[0i8; AV_ERROR_MAX_STRING_SIZE as usize]; 27];
+static mut STRINGS: [[i8; AV_ERROR_MAX_STRING_SIZE as usize]; 39] =
+    [[0i8; AV_ERROR_MAX_STRING_SIZE as usize]; 39];
 
 pub fn register_all() {
     unsafe {
@@ -309,6 +432,69 @@ pub fn register_all() {
             STRINGS[index(&Error::HttpServerError)].as_mut_ptr(),
             AV_ERROR_MAX_STRING_SIZE,
         );
+
+        av_strerror(
+            Error::Again.into(),
+            STRINGS[index(&Error::Again)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::NoMemory.into(),
+            STRINGS[index(&Error::NoMemory)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::InvalidArgument.into(),
+            STRINGS[index(&Error::InvalidArgument)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::Io.into(),
+            STRINGS[index(&Error::Io)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::TimedOut.into(),
+            STRINGS[index(&Error::TimedOut)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::NotFound.into(),
+            STRINGS[index(&Error::NotFound)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::PermissionDenied.into(),
+            STRINGS[index(&Error::PermissionDenied)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::Interrupted.into(),
+            STRINGS[index(&Error::Interrupted)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::BrokenPipe.into(),
+            STRINGS[index(&Error::BrokenPipe)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::ConnectionRefused.into(),
+            STRINGS[index(&Error::ConnectionRefused)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+        av_strerror(
+            Error::ConnectionReset.into(),
+            STRINGS[index(&Error::ConnectionReset)].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
+
+        // the actual message depends on the errno, see `Display`
+        av_strerror(
+            AVERROR_UNKNOWN,
+            STRINGS[index(&Error::Other { errno: 0 })].as_mut_ptr(),
+            AV_ERROR_MAX_STRING_SIZE,
+        );
     }
 }
 
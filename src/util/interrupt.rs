@@ -4,29 +4,73 @@ use std::process;
 use ffi::*;
 use libc::{c_int, c_void};
 
+type Closure = Option<Box<dyn FnMut() -> bool + Send>>;
+
+// The callback points to a heap slot that stays put for the whole lifetime of
+// the `Interrupt`, so the closure can be swapped after libavformat copied the
+// callback into its protocol contexts.
 pub struct Interrupt {
     pub interrupt: AVIOInterruptCB,
+    closure: Box<Closure>,
 }
 
-extern "C" fn callback<F>(opaque: *mut c_void) -> c_int
-where
-    F: FnMut() -> bool,
-{
-    match panic::catch_unwind(|| (unsafe { &mut *(opaque as *mut F) })()) {
-        Ok(ret) => ret as c_int,
-        Err(_) => process::abort(),
+unsafe impl Send for Interrupt {}
+
+extern "C" fn callback(opaque: *mut c_void) -> c_int {
+    let closure = unsafe { &mut *(opaque as *mut Closure) };
+
+    match *closure {
+        Some(ref mut closure) => match panic::catch_unwind(panic::AssertUnwindSafe(closure)) {
+            Ok(ret) => ret as c_int,
+            Err(_) => process::abort(),
+        },
+
+        None => 0,
+    }
+}
+
+impl Interrupt {
+    pub fn empty() -> Self {
+        let mut closure: Box<Closure> = Box::new(None);
+        let interrupt_cb = AVIOInterruptCB {
+            callback: Some(callback),
+            opaque: &mut *closure as *mut Closure as *mut c_void,
+        };
+
+        Interrupt {
+            interrupt: interrupt_cb,
+            closure: closure,
+        }
+    }
+
+    pub fn set<F>(&mut self, closure: F)
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        *self.closure = Some(Box::new(closure));
+    }
+
+    pub fn clear(&mut self) {
+        *self.closure = None;
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.closure.is_some()
+    }
+}
+
+impl Default for Interrupt {
+    fn default() -> Self {
+        Self::empty()
     }
 }
 
 pub fn new<F>(opaque: Box<F>) -> Interrupt
 where
-    F: FnMut() -> bool,
+    F: FnMut() -> bool + Send + 'static,
 {
-    let interrupt_cb = AVIOInterruptCB {
-        callback: Some(callback::<F>),
-        opaque: Box::into_raw(opaque) as *mut c_void,
-    };
-    Interrupt {
-        interrupt: interrupt_cb,
-    }
+    let mut interrupt = Interrupt::empty();
+    *interrupt.closure = Some(opaque);
+
+    interrupt
 }
@@ -1,8 +1,15 @@
+use std::time::Duration;
+
 use ffi::*;
 use {Rational, Rounding};
 
 pub const TIME_BASE: Rational = Rational(AV_TIME_BASE_Q.num, AV_TIME_BASE_Q.den);
 
+// converts to TIME_BASE units, truncating anything below a microsecond
+pub fn from_duration(value: Duration) -> i64 {
+    value.as_secs() as i64 * 1_000_000 + i64::from(value.subsec_nanos()) / 1_000
+}
+
 pub trait Rescale {
     fn rescale<S, D>(&self, source: S, destination: D) -> i64
     where
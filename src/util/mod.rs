@@ -1,5 +1,6 @@
 #[macro_use]
 pub mod dictionary;
+pub mod cancel;
 pub mod channel_layout;
 pub mod chroma;
 pub mod color;